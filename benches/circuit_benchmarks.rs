@@ -0,0 +1,176 @@
+#![allow(non_snake_case)]
+extern crate bulletproofs;
+use bulletproofs::r1cs::{
+    batch_verify, ConstraintSystem, LinearCombination, Prover, R1CSProof, Variable, Verifier,
+};
+use bulletproofs::{BulletproofGens, PedersenGens};
+
+#[macro_use]
+extern crate criterion;
+use criterion::{Criterion, ParameterizedBenchmark, Throughput};
+
+extern crate curve25519_dalek;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+
+extern crate merlin;
+use merlin::Transcript;
+
+extern crate rand;
+
+/// Circuit sizes, in multipliers: 1 to 16384.
+fn multiplier_counts() -> Vec<usize> {
+    (0..15).map(|k| 1 << k).collect()
+}
+
+/// Builds a circuit computing `x^(m+1)` with a chain of `m` multipliers.
+fn power_gadget<CS: ConstraintSystem>(cs: &mut CS, x: Variable, m: usize) {
+    let mut acc: LinearCombination = x.into();
+    for _ in 0..m {
+        let (_, _, o) = cs.multiply(acc, x.into());
+        acc = o.into();
+    }
+}
+
+fn create_circuit_proof(c: &mut Criterion) {
+    c.bench(
+        "R1CS proof creation",
+        ParameterizedBenchmark::new(
+            "multipliers",
+            |b, &m| {
+                let pc_gens = PedersenGens::default();
+                let bp_gens = BulletproofGens::new(m, 1);
+                let x = Scalar::from(3u64);
+                let x_blinding = Scalar::random(&mut rand::thread_rng());
+
+                b.iter(|| {
+                    let mut transcript = Transcript::new(b"CircuitBenchmark");
+                    let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+                    let (_, var) = prover.commit(x, x_blinding);
+                    let mut cs = prover.finalize_inputs();
+                    power_gadget(&mut cs, var, m);
+                    cs.prove().unwrap()
+                })
+            },
+            multiplier_counts(),
+        )
+        .throughput(|&m| Throughput::Elements(m as u32)),
+    );
+}
+
+fn verify_circuit_proof(c: &mut Criterion) {
+    c.bench(
+        "R1CS proof verification",
+        ParameterizedBenchmark::new(
+            "multipliers",
+            |b, &m| {
+                let pc_gens = PedersenGens::default();
+                let bp_gens = BulletproofGens::new(m, 1);
+
+                let (proof, commitment) = {
+                    let mut transcript = Transcript::new(b"CircuitBenchmark");
+                    let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+                    let (commitment, var) =
+                        prover.commit(Scalar::from(3u64), Scalar::random(&mut rand::thread_rng()));
+                    let mut cs = prover.finalize_inputs();
+                    power_gadget(&mut cs, var, m);
+                    (cs.prove().unwrap(), commitment)
+                };
+
+                b.iter(|| {
+                    let mut transcript = Transcript::new(b"CircuitBenchmark");
+                    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+                    let var = verifier.commit(commitment);
+                    let mut cs = verifier.finalize_inputs();
+                    power_gadget(&mut cs, var, m);
+                    cs.verify(&proof).unwrap()
+                })
+            },
+            multiplier_counts(),
+        )
+        .throughput(|&m| Throughput::Elements(m as u32)),
+    );
+}
+
+/// Number of proofs in a verification batch.
+const BATCH_SIZE: usize = 64;
+/// Multipliers in each of the batched proofs.
+const BATCH_MULTIPLIERS: usize = 64;
+
+fn batch_proofs(
+    bp_gens: &BulletproofGens,
+    pc_gens: &PedersenGens,
+) -> Vec<(R1CSProof, CompressedRistretto)> {
+    (0..BATCH_SIZE)
+        .map(|_| {
+            let mut transcript = Transcript::new(b"CircuitBenchmark");
+            let mut prover = Prover::new(bp_gens, pc_gens, &mut transcript);
+            let (commitment, var) =
+                prover.commit(Scalar::from(3u64), Scalar::random(&mut rand::thread_rng()));
+            let mut cs = prover.finalize_inputs();
+            power_gadget(&mut cs, var, BATCH_MULTIPLIERS);
+            (cs.prove().unwrap(), commitment)
+        })
+        .collect()
+}
+
+fn verify_circuit_proofs_individually(c: &mut Criterion) {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(BATCH_MULTIPLIERS, 1);
+    let proofs = batch_proofs(&bp_gens, &pc_gens);
+
+    c.bench_function(
+        "R1CS verification of 64 proofs of 64 multipliers, individually",
+        move |b| {
+            b.iter(|| {
+                for (proof, commitment) in proofs.iter() {
+                    let mut transcript = Transcript::new(b"CircuitBenchmark");
+                    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+                    let var = verifier.commit(*commitment);
+                    let mut cs = verifier.finalize_inputs();
+                    power_gadget(&mut cs, var, BATCH_MULTIPLIERS);
+                    cs.verify(proof).unwrap()
+                }
+            })
+        },
+    );
+}
+
+fn verify_circuit_proofs_in_batch(c: &mut Criterion) {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(BATCH_MULTIPLIERS, 1);
+    let proofs = batch_proofs(&bp_gens, &pc_gens);
+
+    c.bench_function(
+        "R1CS verification of 64 proofs of 64 multipliers, in a batch",
+        move |b| {
+            b.iter(|| {
+                let mut transcripts: Vec<_> = (0..BATCH_SIZE)
+                    .map(|_| Transcript::new(b"CircuitBenchmark"))
+                    .collect();
+                let instances = transcripts.iter_mut().zip(proofs.iter()).map(
+                    |(transcript, (proof, commitment))| {
+                        let mut verifier = Verifier::new(&bp_gens, &pc_gens, transcript);
+                        let var = verifier.commit(*commitment);
+                        let mut cs = verifier.finalize_inputs();
+                        power_gadget(&mut cs, var, BATCH_MULTIPLIERS);
+                        (cs, proof)
+                    },
+                );
+                batch_verify(instances).unwrap()
+            })
+        },
+    );
+}
+
+criterion_group! {
+    name = circuit_benchmarks;
+    config = Criterion::default().sample_size(10);
+    targets =
+    create_circuit_proof,
+    verify_circuit_proof,
+    verify_circuit_proofs_individually,
+    verify_circuit_proofs_in_batch,
+}
+
+criterion_main!(circuit_benchmarks);
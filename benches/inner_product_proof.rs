@@ -0,0 +1,123 @@
+#![allow(non_snake_case)]
+#[macro_use]
+extern crate criterion;
+use criterion::Criterion;
+
+extern crate rand;
+
+extern crate curve25519_dalek;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+
+extern crate merlin;
+use merlin::Transcript;
+
+extern crate bulletproofs;
+use bulletproofs::{BulletproofGens, InnerProductProof, PedersenGens};
+
+static VECTOR_LENGTHS: [usize; 2] = [32, 64];
+
+/// The statement and proof for an inner product of two random
+/// vectors of length `n`.
+struct Instance {
+    proof: InnerProductProof,
+    P: RistrettoPoint,
+    Q: RistrettoPoint,
+    G: Vec<RistrettoPoint>,
+    H: Vec<RistrettoPoint>,
+    Hprime_factors: Vec<Scalar>,
+}
+
+fn instance(n: usize) -> Instance {
+    let bp_gens = BulletproofGens::new(n, 1);
+    let G: Vec<RistrettoPoint> = bp_gens.G(n, 1).cloned().collect();
+    let H: Vec<RistrettoPoint> = bp_gens.H(n, 1).cloned().collect();
+    let Q = PedersenGens::default().B_blinding;
+    let mut rng = rand::thread_rng();
+
+    let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+    let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+    let c = a
+        .iter()
+        .zip(&b)
+        .fold(Scalar::zero(), |acc, (a_i, b_i)| acc + a_i * b_i);
+    let P = RistrettoPoint::vartime_multiscalar_mul(
+        a.iter().chain(&b).chain(Some(&c)),
+        G.iter().chain(&H).chain(Some(&Q)),
+    );
+    let Hprime_factors = vec![Scalar::one(); n];
+
+    let proof = InnerProductProof::create(
+        &mut Transcript::new(b"InnerProductProofBenchmark"),
+        &Q,
+        &Hprime_factors,
+        G.clone(),
+        H.clone(),
+        a,
+        b,
+    );
+
+    Instance {
+        proof,
+        P,
+        Q,
+        G,
+        H,
+        Hprime_factors,
+    }
+}
+
+fn verify_ipp(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "Inner product proof verification",
+        move |b, &&n| {
+            let inst = instance(n);
+            b.iter(|| {
+                let mut transcript = Transcript::new(b"InnerProductProofBenchmark");
+                inst.proof.verify(
+                    n,
+                    &mut transcript,
+                    &inst.Hprime_factors,
+                    &inst.P,
+                    &inst.Q,
+                    &inst.G,
+                    &inst.H,
+                )
+            })
+        },
+        &VECTOR_LENGTHS,
+    );
+}
+
+fn verify_fast_ipp(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "Inner product proof fast verification",
+        move |b, &&n| {
+            let inst = instance(n);
+            b.iter(|| {
+                let mut transcript = Transcript::new(b"InnerProductProofBenchmark");
+                inst.proof.verify_fast(
+                    n,
+                    &mut transcript,
+                    &inst.Hprime_factors,
+                    &inst.P,
+                    &inst.Q,
+                    &inst.G,
+                    &inst.H,
+                )
+            })
+        },
+        &VECTOR_LENGTHS,
+    );
+}
+
+criterion_group! {
+    name = verify_ipp_group;
+    config = Criterion::default();
+    targets =
+    verify_ipp,
+    verify_fast_ipp,
+}
+
+criterion_main!(verify_ipp_group);
@@ -0,0 +1,69 @@
+extern crate bulletproofs;
+use bulletproofs::r1cs::{LinearCombination, Variable};
+
+#[macro_use]
+extern crate criterion;
+use criterion::Criterion;
+
+extern crate curve25519_dalek;
+use curve25519_dalek::scalar::Scalar;
+
+/// Number of terms in the linear combinations.
+const TERMS: usize = 1000;
+
+fn variables() -> Vec<Variable> {
+    (0..TERMS).map(Variable::MultiplierLeft).collect()
+}
+
+fn coefficients() -> Vec<Scalar> {
+    (0..TERMS as u64).map(Scalar::from).collect()
+}
+
+fn add_chain(c: &mut Criterion) {
+    let vars = variables();
+    c.bench_function("LinearCombination: chain 1000 add() calls", move |b| {
+        b.iter(|| {
+            vars.iter()
+                .fold(LinearCombination::default(), |lc, &v| lc + v)
+        })
+    });
+}
+
+fn sub_chain(c: &mut Criterion) {
+    let vars = variables();
+    c.bench_function("LinearCombination: chain 1000 sub() calls", move |b| {
+        b.iter(|| {
+            vars.iter()
+                .fold(LinearCombination::default(), |lc, &v| lc - v)
+        })
+    });
+}
+
+fn mul_scalar(c: &mut Criterion) {
+    let lc = LinearCombination::dot_product(variables(), coefficients());
+    let x = Scalar::from(7u64);
+    c.bench_function(
+        "LinearCombination: multiply 1000 terms by a scalar",
+        move |b| b.iter(|| lc.clone() * x),
+    );
+}
+
+fn dot_product(c: &mut Criterion) {
+    let vars = variables();
+    let coeffs = coefficients();
+    c.bench_function("LinearCombination: dot product of 1000 terms", move |b| {
+        b.iter(|| LinearCombination::dot_product_scalar(&vars, &coeffs))
+    });
+}
+
+criterion_group! {
+    name = lc_benchmarks;
+    config = Criterion::default();
+    targets =
+    add_chain,
+    sub_chain,
+    mul_scalar,
+    dot_product,
+}
+
+criterion_main!(lc_benchmarks);
@@ -1,39 +1,245 @@
 //! Errors related to proving and verifying proofs.
 
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::sync::Arc;
+
 /// Represents an error in proof creation, verification, or parsing.
-#[derive(Fail, Clone, Debug, Eq, PartialEq)]
+///
+/// Errors are ordered by severity, from least to most severe:
+///
+/// 1. [`InvalidBitsize`](ProofError::InvalidBitsize),
+/// 2. [`InvalidAggregation`](ProofError::InvalidAggregation),
+/// 3. [`InvalidGeneratorsLength`](ProofError::InvalidGeneratorsLength),
+/// 4. [`WrongNumBlindingFactors`](ProofError::WrongNumBlindingFactors),
+/// 5. [`CommitmentMismatch`](ProofError::CommitmentMismatch),
+/// 6. [`InvalidBlindingShares`](ProofError::InvalidBlindingShares),
+/// 7. [`IoError`](ProofError::IoError), ordered by error kind and message,
+/// 8. [`FormatError`](ProofError::FormatError),
+/// 9. [`InvalidPoint`](ProofError::InvalidPoint), ordered by byte offset,
+/// 10. [`ProvingError`](ProofError::ProvingError), ordered by the [`MPCError`] it wraps,
+/// 11. [`VerificationError`](ProofError::VerificationError).
+///
+/// That is, errors in the parameters supplied by the caller rank
+/// below malformed or dishonest input, and a proof that fails to
+/// verify ranks highest.  The relative order of these variants is
+/// part of the stable API; new variants will be inserted according to
+/// the same principle.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum ProofError {
     /// This error occurs when a proof failed to verify.
-    #[fail(display = "Proof verification failed.")]
     VerificationError,
     /// This error occurs when the proof encoding is malformed.
-    #[fail(display = "Proof data could not be parsed.")]
     FormatError,
+    /// This error occurs when reading or writing the proof encoding
+    /// fails with the given I/O error, which is also its
+    /// [`source`](Error::source).
+    IoError(ProofIoError),
+    /// This error occurs when verifying a proof whose encoding
+    /// contains a point that is not a valid compressed Ristretto
+    /// point.
+    InvalidPoint {
+        /// The offset of the point in the encoding.
+        byte_offset: usize,
+        /// The proof field containing the point, e.g. `"A"`.
+        field: &'static str,
+        /// The index of the point, for vectors of points such as `"L"`.
+        index: Option<usize>,
+    },
     /// This error occurs during proving if the number of blinding
     /// factors does not match the number of values.
-    #[fail(display = "Wrong number of blinding factors supplied.")]
     WrongNumBlindingFactors,
     /// This error occurs when attempting to create a proof with
     /// bitsize other than \\(8\\), \\(16\\), \\(32\\), or \\(64\\).
-    #[fail(display = "Invalid bitsize, must have n = 8,16,32,64.")]
     InvalidBitsize,
     /// This error occurs when attempting to create an aggregated
     /// proof with non-power-of-two aggregation size.
-    #[fail(display = "Invalid aggregation size, m must be a power of 2.")]
     InvalidAggregation,
     /// This error occurs when there are insufficient generators for the proof.
-    #[fail(display = "Invalid generators size, too few generators for proof")]
     InvalidGeneratorsLength,
+    /// This error occurs during proving if a supplied commitment does
+    /// not open to the supplied value and blinding factor.
+    CommitmentMismatch,
+    /// This error occurs when reconstructing a blinding factor from
+    /// fewer [`BlindingShare`](::range_proof_mpc::blinding::BlindingShare)s
+    /// than the threshold, or from shares with a zero or repeated index.
+    InvalidBlindingShares,
     /// This error results from an internal error during proving.
     ///
     /// The single-party prover is implemented by performing
     /// multiparty computation with ourselves.  However, because the
     /// MPC protocol is not exposed by the single-party API, we
     /// consider its errors to be internal errors.
-    #[fail(display = "Internal error during proof creation: {}", _0)]
     ProvingError(MPCError),
 }
 
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProofError::VerificationError => write!(f, "Proof verification failed."),
+            ProofError::FormatError => write!(f, "Proof data could not be parsed."),
+            ProofError::IoError(e) => write!(f, "I/O error on proof data: {}", e.get_ref()),
+            ProofError::InvalidPoint {
+                byte_offset,
+                field,
+                index: Some(index),
+            } => write!(
+                f,
+                "Proof data has an invalid point {}[{}] at byte offset {}.",
+                field, index, byte_offset
+            ),
+            ProofError::InvalidPoint {
+                byte_offset,
+                field,
+                index: None,
+            } => write!(
+                f,
+                "Proof data has an invalid point {} at byte offset {}.",
+                field, byte_offset
+            ),
+            ProofError::WrongNumBlindingFactors => {
+                write!(f, "Wrong number of blinding factors supplied.")
+            }
+            ProofError::InvalidBitsize => write!(f, "Invalid bitsize, must have n = 8,16,32,64."),
+            ProofError::InvalidAggregation => {
+                write!(f, "Invalid aggregation size, m must be a power of 2.")
+            }
+            ProofError::InvalidGeneratorsLength => {
+                write!(f, "Invalid generators size, too few generators for proof")
+            }
+            ProofError::CommitmentMismatch => {
+                write!(f, "Commitment does not match the value and blinding.")
+            }
+            ProofError::InvalidBlindingShares => {
+                write!(f, "Too few or invalid blinding shares supplied.")
+            }
+            ProofError::ProvingError(e) => write!(f, "Internal error during proof creation: {}", e),
+        }
+    }
+}
+
+impl Error for ProofError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProofError::ProvingError(e) => Some(e),
+            ProofError::IoError(e) => Some(e.get_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl ProofError {
+    /// Returns the rank of the error in the severity ordering.
+    fn severity(&self) -> u8 {
+        match self {
+            ProofError::InvalidBitsize => 0,
+            ProofError::InvalidAggregation => 1,
+            ProofError::InvalidGeneratorsLength => 2,
+            ProofError::WrongNumBlindingFactors => 3,
+            ProofError::CommitmentMismatch => 4,
+            ProofError::InvalidBlindingShares => 5,
+            ProofError::IoError(_) => 6,
+            ProofError::FormatError => 7,
+            ProofError::InvalidPoint { .. } => 8,
+            ProofError::ProvingError(_) => 9,
+            ProofError::VerificationError => 10,
+        }
+    }
+}
+
+impl PartialOrd for ProofError {
+    fn partial_cmp(&self, other: &ProofError) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ProofError {
+    fn cmp(&self, other: &ProofError) -> Ordering {
+        match (self, other) {
+            (
+                ProofError::InvalidPoint {
+                    byte_offset: a_offset,
+                    field: a_field,
+                    index: a_index,
+                },
+                ProofError::InvalidPoint {
+                    byte_offset: b_offset,
+                    field: b_field,
+                    index: b_index,
+                },
+            ) => (a_offset, a_field, a_index).cmp(&(b_offset, b_field, b_index)),
+            (ProofError::IoError(a), ProofError::IoError(b)) => a.cmp(b),
+            (ProofError::ProvingError(a), ProofError::ProvingError(b)) => a.cmp(b),
+            _ => self.severity().cmp(&other.severity()),
+        }
+    }
+}
+
+impl From<io::Error> for ProofError {
+    fn from(e: io::Error) -> ProofError {
+        ProofError::IoError(e.into())
+    }
+}
+
+/// An [`io::Error`] in a [`ProofError::IoError`].
+///
+/// Since `io::Error` cannot be cloned or compared, it is shared, and
+/// errors are compared, ordered and hashed by their
+/// [`io::ErrorKind`] and message.
+#[derive(Clone, Debug)]
+pub struct ProofIoError(Arc<io::Error>);
+
+impl ProofIoError {
+    /// Returns the kind of the I/O error.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.0.kind()
+    }
+
+    /// Returns the I/O error.
+    pub fn get_ref(&self) -> &io::Error {
+        &self.0
+    }
+
+    fn key(&self) -> (io::ErrorKind, String) {
+        (self.0.kind(), self.0.to_string())
+    }
+}
+
+impl From<io::Error> for ProofIoError {
+    fn from(e: io::Error) -> ProofIoError {
+        ProofIoError(Arc::new(e))
+    }
+}
+
+impl PartialEq for ProofIoError {
+    fn eq(&self, other: &ProofIoError) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ProofIoError {}
+
+impl PartialOrd for ProofIoError {
+    fn partial_cmp(&self, other: &ProofIoError) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ProofIoError {
+    fn cmp(&self, other: &ProofIoError) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for ProofIoError {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
 impl From<MPCError> for ProofError {
     fn from(e: MPCError) -> ProofError {
         match e {
@@ -52,59 +258,248 @@ impl From<MPCError> for ProofError {
 /// API: although the MPC protocol is used internally for single-party
 /// proving, its API should not expose the complexity of the MPC
 /// protocol.
-#[derive(Fail, Clone, Debug, Eq, PartialEq)]
+///
+/// Errors are ordered in the order of declaration of the variants.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub enum MPCError {
     /// This error occurs when the dealer gives a zero challenge,
     /// which would annihilate the blinding factors.
-    #[fail(display = "Dealer gave a malicious challenge value.")]
     MaliciousDealer,
     /// This error occurs when attempting to create a proof with
     /// bitsize other than \\(8\\), \\(16\\), \\(32\\), or \\(64\\).
-    #[fail(display = "Invalid bitsize, must have n = 8,16,32,64")]
     InvalidBitsize,
     /// This error occurs when attempting to create an aggregated
     /// proof with non-power-of-two aggregation size.
-    #[fail(display = "Invalid aggregation size, m must be a power of 2")]
     InvalidAggregation,
     /// This error occurs when there are insufficient generators for the proof.
-    #[fail(display = "Invalid generators size, too few generators for proof")]
     InvalidGeneratorsLength,
     /// This error occurs when the dealer is given the wrong number of
     /// value commitments.
-    #[fail(display = "Wrong number of value commitments")]
     WrongNumBitCommitments,
     /// This error occurs when the dealer is given the wrong number of
     /// polynomial commitments.
-    #[fail(display = "Wrong number of value commitments")]
     WrongNumPolyCommitments,
     /// This error occurs when the dealer is given the wrong number of
     /// proof shares.
-    #[fail(display = "Wrong number of proof shares")]
     WrongNumProofShares,
     /// This error occurs when one or more parties submit malformed
     /// proof shares.
-    #[fail(display = "Malformed proof shares from parties {:?}", bad_shares)]
     MalformedProofShares {
         /// A vector with the indexes of the parties whose shares were malformed.
         bad_shares: Vec<usize>,
     },
 }
 
+impl fmt::Display for MPCError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MPCError::MaliciousDealer => write!(f, "Dealer gave a malicious challenge value."),
+            MPCError::InvalidBitsize => write!(f, "Invalid bitsize, must have n = 8,16,32,64"),
+            MPCError::InvalidAggregation => {
+                write!(f, "Invalid aggregation size, m must be a power of 2")
+            }
+            MPCError::InvalidGeneratorsLength => {
+                write!(f, "Invalid generators size, too few generators for proof")
+            }
+            MPCError::WrongNumBitCommitments => write!(f, "Wrong number of value commitments"),
+            MPCError::WrongNumPolyCommitments => write!(f, "Wrong number of value commitments"),
+            MPCError::WrongNumProofShares => write!(f, "Wrong number of proof shares"),
+            MPCError::MalformedProofShares { bad_shares } => {
+                write!(f, "Malformed proof shares from parties {:?}", bad_shares)
+            }
+        }
+    }
+}
+
+impl Error for MPCError {}
+
 /// Represents an error during the proving or verifying of a constraint system.
 #[cfg(feature = "yoloproofs")]
-#[derive(Fail, Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum R1CSError {
     /// Occurs when there are insufficient generators for the proof.
-    #[fail(display = "Invalid generators size, too few generators for proof")]
     InvalidGeneratorsLength,
     /// Occurs when verification of an
     /// [`R1CSProof`](::r1cs::R1CSProof) fails.
-    #[fail(display = "R1CSProof did not verify correctly.")]
     VerificationError,
 
+    /// Occurs when committing to a batch of high-level variables
+    /// with a different number of values and blinding factors.
+    WrongNumBlindingFactors,
+
     /// Occurs when trying to use a missing variable assignment.
     /// Used by gadgets that build the constraint system to signal that
     /// a variable assignment is not provided when the prover needs it.
-    #[fail(display = "Variable does not have a value assignment.")]
     MissingAssignment,
+
+    /// Occurs when [`ConstraintSystem::assert_nonzero`](::r1cs::ConstraintSystem::assert_nonzero)
+    /// is called on a variable whose assignment is zero, so that no
+    /// witness can satisfy the constraint.
+    WitnessIsZero,
+
+    /// Occurs when the proofs passed to
+    /// [`batch_verify`](::r1cs::batch_verify) were not all created with
+    /// the same generators.
+    GeneratorsMismatch,
+
+    /// Occurs when a [`BudgetedCS`](::r1cs::BudgetedCS) would need more
+    /// multipliers than its budget allows.
+    BudgetExceeded {
+        /// The number of multipliers needed.
+        used: usize,
+        /// The maximum number of multipliers allowed.
+        budget: usize,
+    },
+}
+
+#[cfg(feature = "yoloproofs")]
+impl fmt::Display for R1CSError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            R1CSError::InvalidGeneratorsLength => {
+                write!(f, "Invalid generators size, too few generators for proof")
+            }
+            R1CSError::VerificationError => write!(f, "R1CSProof did not verify correctly."),
+            R1CSError::WrongNumBlindingFactors => {
+                write!(f, "Wrong number of blinding factors supplied.")
+            }
+            R1CSError::MissingAssignment => write!(f, "Variable does not have a value assignment."),
+            R1CSError::WitnessIsZero => write!(f, "Variable asserted to be nonzero is zero."),
+            R1CSError::GeneratorsMismatch => {
+                write!(f, "Proofs in a batch use different generators.")
+            }
+            R1CSError::BudgetExceeded { used, budget } => write!(
+                f,
+                "Constraint system needs {} multipliers, but the budget is {}.",
+                used, budget
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "yoloproofs")]
+impl Error for R1CSError {}
+
+// Errors are routinely moved across threads (e.g. out of a spawned
+// prover), so make sure they stay `Send + Sync`.
+#[allow(dead_code)]
+fn assert_send_sync() {
+    fn assert<T: Send + Sync + 'static>() {}
+    assert::<ProofError>();
+    assert::<MPCError>();
+    #[cfg(feature = "yoloproofs")]
+    assert::<R1CSError>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use failure::Fail;
+    use std::collections::HashMap;
+
+    fn all_proof_errors() -> Vec<ProofError> {
+        vec![
+            ProofError::InvalidBitsize,
+            ProofError::InvalidAggregation,
+            ProofError::InvalidGeneratorsLength,
+            ProofError::WrongNumBlindingFactors,
+            ProofError::CommitmentMismatch,
+            ProofError::InvalidBlindingShares,
+            io::Error::new(io::ErrorKind::NotFound, "a").into(),
+            io::Error::new(io::ErrorKind::NotFound, "b").into(),
+            io::Error::from(io::ErrorKind::UnexpectedEof).into(),
+            ProofError::FormatError,
+            ProofError::InvalidPoint {
+                byte_offset: 0,
+                field: "A",
+                index: None,
+            },
+            ProofError::InvalidPoint {
+                byte_offset: 32,
+                field: "S",
+                index: None,
+            },
+            ProofError::InvalidPoint {
+                byte_offset: 7 * 32,
+                field: "L",
+                index: Some(0),
+            },
+            ProofError::ProvingError(MPCError::MaliciousDealer),
+            ProofError::ProvingError(MPCError::WrongNumProofShares),
+            ProofError::ProvingError(MPCError::MalformedProofShares {
+                bad_shares: vec![0],
+            }),
+            ProofError::ProvingError(MPCError::MalformedProofShares {
+                bad_shares: vec![1],
+            }),
+            ProofError::VerificationError,
+        ]
+    }
+
+    #[test]
+    fn proof_error_severity_ordering() {
+        let errors = all_proof_errors();
+
+        // The list above is in increasing order of severity.
+        for (i, a) in errors.iter().enumerate() {
+            for (j, b) in errors.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{:?} vs {:?}", a, b);
+                assert_eq!(a.partial_cmp(b), Some(a.cmp(b)));
+                assert_eq!(a == b, a.cmp(b) == Ordering::Equal);
+            }
+        }
+
+        // Transitivity
+        for a in errors.iter() {
+            for b in errors.iter().filter(|b| a <= *b) {
+                for c in errors.iter().filter(|c| b <= *c) {
+                    assert!(a <= c);
+                }
+            }
+        }
+
+        assert_eq!(
+            errors.iter().cloned().max(),
+            Some(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn proof_error_hash() {
+        let mut counts: HashMap<ProofError, usize> = HashMap::new();
+        for e in all_proof_errors()
+            .into_iter()
+            .chain(vec![ProofError::FormatError, ProofError::FormatError])
+        {
+            *counts.entry(e).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), all_proof_errors().len());
+        assert_eq!(counts[&ProofError::FormatError], 3);
+        assert_eq!(counts[&ProofError::VerificationError], 1);
+    }
+
+    #[test]
+    fn proving_error_exposes_source() {
+        let e = ProofError::ProvingError(MPCError::MaliciousDealer);
+        assert_eq!(
+            e.to_string(),
+            "Internal error during proof creation: Dealer gave a malicious challenge value."
+        );
+        assert_eq!(
+            e.source().map(|s| s.to_string()),
+            Some(MPCError::MaliciousDealer.to_string())
+        );
+        assert!(ProofError::FormatError.source().is_none());
+
+        let e: ProofError = io::Error::new(io::ErrorKind::BrokenPipe, "closed").into();
+        assert_eq!(e.to_string(), "I/O error on proof data: closed");
+        assert_eq!(
+            e.source().map(|s| s.to_string()),
+            Some("closed".to_string())
+        );
+
+        // Errors remain usable as `failure::Fail` through its blanket impl.
+        let failure: &dyn Fail = &e;
+        assert_eq!(failure.to_string(), e.to_string());
+    }
 }
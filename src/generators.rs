@@ -6,13 +6,15 @@
 
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
-use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::MultiscalarMul;
 
 use digest::{ExtendableOutput, Input, XofReader};
 use sha3::{Sha3XofReader, Sha3_512, Shake256};
 
+use errors::ProofError;
+
 /// Represents a pair of base points for Pedersen commitments.
 ///
 /// The Bulletproofs implementation and API is designed to support
@@ -34,12 +36,60 @@ pub struct PedersenGens {
 
 impl PedersenGens {
     /// Creates a Pedersen commitment using the value scalar and a blinding factor.
+    ///
+    /// The commitment is \\(\texttt{value} \cdot B + \texttt{blinding} \cdot \widetilde{B}\\).
     pub fn commit(&self, value: Scalar, blinding: Scalar) -> RistrettoPoint {
         RistrettoPoint::multiscalar_mul(&[value, blinding], &[self.B, self.B_blinding])
     }
+
+    /// Creates compressed Pedersen commitments to each of the `values`
+    /// using the corresponding `blindings`.
+    ///
+    /// Returns [`ProofError::WrongNumBlindingFactors`] if there are not
+    /// as many blindings as values.
+    pub fn commit_batch(
+        &self,
+        values: &[Scalar],
+        blindings: &[Scalar],
+    ) -> Result<Vec<CompressedRistretto>, ProofError> {
+        if values.len() != blindings.len() {
+            return Err(ProofError::WrongNumBlindingFactors);
+        }
+        Ok(values
+            .iter()
+            .zip(blindings)
+            .map(|(v, v_blinding)| self.commit(*v, *v_blinding).compress())
+            .collect())
+    }
+
+    /// Creates a compressed commitment to a public value, with a zero
+    /// blinding factor.
+    ///
+    /// Such a commitment hides nothing, and can be used to include a
+    /// known value in the homomorphic sum of a set of commitments.
+    pub fn commit_known(&self, value: Scalar) -> CompressedRistretto {
+        (value * self.B).compress()
+    }
+
+    /// Returns `n` generators with no known discrete log relations
+    /// between them or to the default \\(B\\) and \\(\widetilde{B}\\).
+    ///
+    /// The generators are the first `n` points of the chain derived
+    /// by [`BulletproofGens`] for the label
+    /// `"PedersenGens independent generators"`: SHAKE256 is applied
+    /// to `"GeneratorsChain"` followed by the label, and each 64 bytes
+    /// of XOF output are fed into the `ristretto255` hash-to-group
+    /// function.
+    pub fn independent_generators(n: usize) -> Vec<RistrettoPoint> {
+        GeneratorsChain::new(b"PedersenGens independent generators")
+            .take(n)
+            .collect()
+    }
 }
 
 impl Default for PedersenGens {
+    /// Uses the Ristretto basepoint as \\(B\\), and the point obtained
+    /// by hashing the basepoint's encoding as \\(\widetilde{B}\\).
     fn default() -> Self {
         PedersenGens {
             B: RISTRETTO_BASEPOINT_POINT,
@@ -142,34 +192,186 @@ impl BulletproofGens {
     /// * `party_capacity` is the maximum number of parties that can
     ///    produce an aggregated proof.
     pub fn new(gens_capacity: usize, party_capacity: usize) -> Self {
+        BulletproofGens::from_chains(gens_capacity, party_capacity, |generator, i| {
+            GeneratorsChain::new(&party_label(generator, i))
+        })
+    }
+
+    /// Create a `BulletproofGens` object whose generator chains are
+    /// additionally domain-separated by `seed`, for tests that need
+    /// a reproducible generator set distinct from the default one.
+    ///
+    /// The generators are derived as in [`BulletproofGens::new`],
+    /// with the seed appended to each chain's label, and the same
+    /// seed always produces the same generators.
+    ///
+    /// These generators are **not** suitable for production use:
+    /// proofs made with them do not verify with the standard
+    /// generators, and their encoding is rejected by
+    /// [`BulletproofGens::from_bytes`].
+    pub fn from_seed(seed: u64, gens_capacity: usize, party_capacity: usize) -> Self {
         use byteorder::{ByteOrder, LittleEndian};
 
+        BulletproofGens::from_chains(gens_capacity, party_capacity, |generator, i| {
+            let mut label = [0u8; 5 + 4 + 8];
+            label[0..5].copy_from_slice(&party_label(generator, i));
+            label[5..9].copy_from_slice(b"seed");
+            LittleEndian::write_u64(&mut label[9..17], seed);
+            GeneratorsChain::new(&label)
+        })
+    }
+
+    /// Takes the first `gens_capacity` generators of the `G` and `H`
+    /// chains of each party, as produced by `chain`.
+    fn from_chains<F>(gens_capacity: usize, party_capacity: usize, chain: F) -> Self
+    where
+        F: Fn(u8, usize) -> GeneratorsChain,
+    {
+        let chains = |generator: u8| -> Vec<Vec<RistrettoPoint>> {
+            (0..party_capacity)
+                .map(|i| chain(generator, i).take(gens_capacity).collect())
+                .collect()
+        };
         BulletproofGens {
             gens_capacity,
             party_capacity,
-            G_vec: (0..party_capacity)
-                .map(|i| {
-                    let party_index = i as u32;
-                    let mut label = [b'G', 0, 0, 0, 0];
-                    LittleEndian::write_u32(&mut label[1..5], party_index);
+            G_vec: chains(b'G'),
+            H_vec: chains(b'H'),
+        }
+    }
 
-                    GeneratorsChain::new(&label)
-                        .take(gens_capacity)
-                        .collect::<Vec<_>>()
-                })
-                .collect(),
-            H_vec: (0..party_capacity)
-                .map(|i| {
-                    let party_index = i as u32;
-                    let mut label = [b'H', 0, 0, 0, 0];
-                    LittleEndian::write_u32(&mut label[1..5], party_index);
+    /// Returns the generators for the first `m` parties, limited to
+    /// the first `n` generators for each party.
+    ///
+    /// Since each party's generators are the prefix of a fixed chain,
+    /// the result is identical to `BulletproofGens::new(n, m)`, and
+    /// proofs created with either set verify with the other, but it
+    /// is obtained by copying the precomputed points rather than
+    /// recomputing them.
+    ///
+    /// Panics if `n` exceeds `gens_capacity` or `m` exceeds
+    /// `party_capacity`.
+    pub fn subset(&self, n: usize, m: usize) -> BulletproofGens {
+        assert!(
+            n <= self.gens_capacity && m <= self.party_capacity,
+            "BulletproofGens::subset: subset exceeds the generators' capacity"
+        );
+        let prefix = |vec: &[Vec<RistrettoPoint>]| -> Vec<Vec<RistrettoPoint>> {
+            vec.iter().take(m).map(|gens| gens[..n].to_vec()).collect()
+        };
+        BulletproofGens {
+            gens_capacity: n,
+            party_capacity: m,
+            G_vec: prefix(&self.G_vec),
+            H_vec: prefix(&self.H_vec),
+        }
+    }
+
+    /// Serializes the generators into a byte array, so that a large
+    /// generator table can be precomputed once and loaded quickly.
+    ///
+    /// # Layout
+    ///
+    /// The layout of the generators encoding is:
+    ///
+    /// * `gens_capacity` and `party_capacity` as little-endian `u64`s,
+    /// * for each party, its `gens_capacity` compressed \\(\mathbf G\\) generators,
+    /// * for each party, its `gens_capacity` compressed \\(\mathbf H\\) generators.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        use byteorder::{ByteOrder, LittleEndian};
 
-                    GeneratorsChain::new(&label)
+        let mut buf = vec![0u8; 16];
+        LittleEndian::write_u64(&mut buf[0..8], self.gens_capacity as u64);
+        LittleEndian::write_u64(&mut buf[8..16], self.party_capacity as u64);
+
+        buf.reserve(2 * self.gens_capacity * self.party_capacity * 32);
+        for chain in self.G_vec.iter().chain(self.H_vec.iter()) {
+            for point in chain {
+                buf.extend_from_slice(point.compress().as_bytes());
+            }
+        }
+        buf
+    }
+
+    /// Deserializes the generators from a byte slice produced by
+    /// [`BulletproofGens::to_bytes`].
+    ///
+    /// Returns [`ProofError::FormatError`] in the following cases:
+    /// * either encoded capacity is zero,
+    /// * the slice length does not match the encoded capacities,
+    /// * any of the points is not a valid compressed Ristretto point,
+    /// * the first generator of any party's chains does not match
+    ///   the generator that [`BulletproofGens::new`] would produce.
+    ///
+    /// The last check only samples the start of each chain, so that
+    /// loading remains fast; it guards against truncated, reordered
+    /// or foreign generator tables, not against deliberate tampering.
+    pub fn from_bytes(slice: &[u8]) -> Result<BulletproofGens, ProofError> {
+        use byteorder::{ByteOrder, LittleEndian};
+        use util::read32;
+
+        if slice.len() < 16 {
+            return Err(ProofError::FormatError);
+        }
+        let gens_capacity = LittleEndian::read_u64(&slice[0..8]) as usize;
+        let party_capacity = LittleEndian::read_u64(&slice[8..16]) as usize;
+        if gens_capacity == 0 || party_capacity == 0 {
+            return Err(ProofError::FormatError);
+        }
+
+        let num_points = gens_capacity
+            .checked_mul(party_capacity)
+            .and_then(|n| n.checked_mul(2))
+            .ok_or(ProofError::FormatError)?;
+        if Some(slice.len() - 16) != num_points.checked_mul(32) {
+            return Err(ProofError::FormatError);
+        }
+
+        let mut points = slice[16..].chunks(32).map(|chunk| {
+            CompressedRistretto(read32(chunk))
+                .decompress()
+                .ok_or(ProofError::FormatError)
+        });
+
+        let mut read_party_chains = |label: u8| -> Result<Vec<Vec<RistrettoPoint>>, ProofError> {
+            (0..party_capacity)
+                .map(|i| {
+                    let chain = points
+                        .by_ref()
                         .take(gens_capacity)
-                        .collect::<Vec<_>>()
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if let Some(first) = chain.first() {
+                        if GeneratorsChain::new(&party_label(label, i)).next() != Some(*first) {
+                            return Err(ProofError::FormatError);
+                        }
+                    }
+                    Ok(chain)
                 })
-                .collect(),
-        }
+                .collect()
+        };
+
+        let G_vec = read_party_chains(b'G')?;
+        let H_vec = read_party_chains(b'H')?;
+
+        Ok(BulletproofGens {
+            gens_capacity,
+            party_capacity,
+            G_vec,
+            H_vec,
+        })
+    }
+
+    /// Returns the largest range proof bitsize \\(n\\) supported by
+    /// these generators, or `0` if they cannot be used for range proofs.
+    ///
+    /// The result is one of \\(0, 8, 16, 32, 64\\), and never exceeds
+    /// [`RANGE_PROOF_MAX_BITS`](::range_proof::RANGE_PROOF_MAX_BITS).
+    pub fn max_range_bits(&self) -> usize {
+        [64, 32, 16, 8]
+            .iter()
+            .cloned()
+            .find(|&n| n <= self.gens_capacity)
+            .unwrap_or(0)
     }
 
     /// Returns j-th share of generators, with an appropriate
@@ -182,7 +384,12 @@ impl BulletproofGens {
     }
 
     /// Return an iterator over the aggregation of the parties' G generators with given size `n`.
-    pub(crate) fn G(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
+    ///
+    /// These are the first `n` generators of each of the first `m`
+    /// parties, in party order, as used by an aggregated range proof
+    /// for `m` values of `n` bits each.  Requires `n <= gens_capacity`
+    /// and `m <= party_capacity`.
+    pub fn G(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
         AggregatedGensIter {
             n,
             m,
@@ -193,7 +400,9 @@ impl BulletproofGens {
     }
 
     /// Return an iterator over the aggregation of the parties' H generators with given size `n`.
-    pub(crate) fn H(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
+    ///
+    /// See [`BulletproofGens::G`] for the order of the generators.
+    pub fn H(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
         AggregatedGensIter {
             n,
             m,
@@ -202,6 +411,69 @@ impl BulletproofGens {
             gen_idx: 0,
         }
     }
+
+    /// Return an iterator over the first `n` G generators of the
+    /// party with index `party_idx`, as used by that party's share of
+    /// an aggregated proof.
+    ///
+    /// Panics if `n` exceeds `gens_capacity` or `party_idx` is not
+    /// less than `party_capacity`.
+    pub fn G_iter(&self, n: usize, party_idx: usize) -> impl Iterator<Item = &RistrettoPoint> {
+        assert!(
+            n <= self.gens_capacity && party_idx < self.party_capacity,
+            "BulletproofGens::G_iter: generators out of range"
+        );
+        self.share(party_idx).G(n)
+    }
+
+    /// Return an iterator over the first `n` H generators of the
+    /// party with index `party_idx`; see [`BulletproofGens::G_iter`].
+    pub fn H_iter(&self, n: usize, party_idx: usize) -> impl Iterator<Item = &RistrettoPoint> {
+        assert!(
+            n <= self.gens_capacity && party_idx < self.party_capacity,
+            "BulletproofGens::H_iter: generators out of range"
+        );
+        self.share(party_idx).H(n)
+    }
+
+    /// Return the generators of [`BulletproofGens::G`] in compressed form.
+    pub fn G_compressed(
+        &self,
+        n: usize,
+        m: usize,
+    ) -> impl Iterator<Item = CompressedRistretto> + '_ {
+        self.G(n, m).map(|G_i| G_i.compress())
+    }
+
+    /// Return the generators of [`BulletproofGens::H`] in compressed form.
+    pub fn H_compressed(
+        &self,
+        n: usize,
+        m: usize,
+    ) -> impl Iterator<Item = CompressedRistretto> + '_ {
+        self.H(n, m).map(|H_i| H_i.compress())
+    }
+}
+
+impl Default for BulletproofGens {
+    /// Creates generators for a single 64-bit range proof, currently
+    /// equivalent to `BulletproofGens::new(64, 1)`.
+    ///
+    /// The default capacity may change in future versions; use
+    /// [`BulletproofGens::new`] if a specific capacity is required.
+    fn default() -> Self {
+        BulletproofGens::new(64, 1)
+    }
+}
+
+/// Returns the domain separation label for the `party_index`-th
+/// chain of `G` or `H` generators.
+fn party_label(generator: u8, party_index: usize) -> [u8; 5] {
+    use byteorder::{ByteOrder, LittleEndian};
+
+    let mut label = [generator, 0, 0, 0, 0];
+    LittleEndian::write_u32(&mut label[1..5], party_index as u32);
+    label
 }
 
 struct AggregatedGensIter<'a> {
@@ -268,6 +540,31 @@ impl<'a> BulletproofGensShare<'a> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn independent_generators_derivation() {
+        let gens = PedersenGens::independent_generators(3);
+        assert_eq!(gens.len(), 3);
+
+        let mut shake = Shake256::default();
+        shake.input(b"GeneratorsChainPedersenGens independent generators");
+        let mut reader = shake.xof_result();
+        for expected in gens.iter().take(2) {
+            let mut uniform_bytes = [0u8; 64];
+            reader.read(&mut uniform_bytes);
+            assert_eq!(
+                *expected,
+                RistrettoPoint::from_uniform_bytes(&uniform_bytes)
+            );
+        }
+
+        let pc_gens = PedersenGens::default();
+        for G in gens.iter() {
+            assert!(*G != pc_gens.B && *G != pc_gens.B_blinding);
+        }
+        assert!(gens[0] != gens[1] && gens[1] != gens[2]);
+        assert_eq!(PedersenGens::independent_generators(2), gens[..2].to_vec());
+    }
+
     #[test]
     fn aggregated_gens_iter_matches_flat_map() {
         let gens = BulletproofGens::new(64, 8);
@@ -308,4 +605,154 @@ mod tests {
         helper(16, 2);
         helper(16, 1);
     }
+
+    #[test]
+    fn aggregated_gens_match_party_shares() {
+        let gens = BulletproofGens::new(16, 4);
+
+        let G: Vec<_> = gens.G(8, 3).cloned().collect();
+        let H: Vec<_> = gens.H(8, 3).cloned().collect();
+        let share_G: Vec<_> = (0..3).flat_map(|j| gens.share(j).G(8)).cloned().collect();
+        let share_H: Vec<_> = (0..3).flat_map(|j| gens.share(j).H(8)).cloned().collect();
+        assert_eq!(G, share_G);
+        assert_eq!(H, share_H);
+
+        let iter_G: Vec<_> = (0..3).flat_map(|j| gens.G_iter(8, j)).cloned().collect();
+        let iter_H: Vec<_> = (0..3).flat_map(|j| gens.H_iter(8, j)).cloned().collect();
+        assert_eq!(G, iter_G);
+        assert_eq!(H, iter_H);
+        assert_eq!(gens.G_iter(16, 3).count(), 16);
+
+        let G_compressed: Vec<_> = gens.G_compressed(8, 3).collect();
+        let H_compressed: Vec<_> = gens.H_compressed(8, 3).collect();
+        assert_eq!(G_compressed.len(), 24);
+        for (P, P_compressed) in G
+            .iter()
+            .chain(&H)
+            .zip(G_compressed.iter().chain(&H_compressed))
+        {
+            assert_eq!(P.compress(), *P_compressed);
+        }
+    }
+
+    #[test]
+    fn pedersen_commit_batch() {
+        let pc_gens = PedersenGens::default();
+        let values: Vec<_> = (0..4u64).map(Scalar::from).collect();
+        let blindings: Vec<_> = (10..14u64).map(Scalar::from).collect();
+
+        let commitments = pc_gens.commit_batch(&values, &blindings).unwrap();
+        assert_eq!(commitments.len(), 4);
+        for ((V, v), v_blinding) in commitments.iter().zip(&values).zip(&blindings) {
+            assert_eq!(*V, pc_gens.commit(*v, *v_blinding).compress());
+        }
+        assert_eq!(
+            pc_gens.commit_batch(&values, &blindings[1..]),
+            Err(ProofError::WrongNumBlindingFactors)
+        );
+        assert!(pc_gens.commit_batch(&[], &[]).unwrap().is_empty());
+
+        assert_eq!(
+            pc_gens.commit_known(values[3]),
+            pc_gens.commit(values[3], Scalar::zero()).compress()
+        );
+    }
+
+    #[test]
+    fn subset_matches_new() {
+        let gens = BulletproofGens::new(64, 4);
+        let subset = gens.subset(16, 2);
+        let expected = BulletproofGens::new(16, 2);
+
+        assert_eq!(subset.gens_capacity, 16);
+        assert_eq!(subset.party_capacity, 2);
+        assert_eq!(subset.G_vec, expected.G_vec);
+        assert_eq!(subset.H_vec, expected.H_vec);
+    }
+
+    #[test]
+    fn from_seed_is_deterministic() {
+        let gens = BulletproofGens::from_seed(7, 8, 2);
+        let same = BulletproofGens::from_seed(7, 8, 2);
+        assert_eq!(gens.G_vec, same.G_vec);
+        assert_eq!(gens.H_vec, same.H_vec);
+
+        let other = BulletproofGens::from_seed(8, 8, 2);
+        assert_ne!(gens.G_vec, other.G_vec);
+        assert_ne!(gens.G_vec, BulletproofGens::new(8, 2).G_vec);
+
+        assert_eq!(
+            BulletproofGens::from_bytes(&gens.to_bytes()).err(),
+            Some(ProofError::FormatError)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "generators out of range")]
+    fn party_gens_out_of_range() {
+        BulletproofGens::new(16, 2).G_iter(8, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "subset exceeds the generators' capacity")]
+    fn subset_exceeding_capacity() {
+        BulletproofGens::new(16, 1).subset(16, 2);
+    }
+
+    #[test]
+    fn max_range_bits_for_capacity() {
+        assert_eq!(BulletproofGens::new(0, 1).max_range_bits(), 0);
+        assert_eq!(BulletproofGens::new(7, 1).max_range_bits(), 0);
+        assert_eq!(BulletproofGens::new(8, 1).max_range_bits(), 8);
+        assert_eq!(BulletproofGens::new(40, 1).max_range_bits(), 32);
+        assert_eq!(BulletproofGens::new(64, 1).max_range_bits(), 64);
+        assert_eq!(BulletproofGens::new(256, 1).max_range_bits(), 64);
+    }
+
+    #[test]
+    fn gens_to_bytes_roundtrip() {
+        let gens = BulletproofGens::new(16, 4);
+        let bytes = gens.to_bytes();
+        assert_eq!(bytes.len(), 16 + 2 * 16 * 4 * 32);
+
+        let decoded = BulletproofGens::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.gens_capacity, 16);
+        assert_eq!(decoded.party_capacity, 4);
+        assert_eq!(decoded.G_vec, gens.G_vec);
+        assert_eq!(decoded.H_vec, gens.H_vec);
+
+        // Empty generator tables cannot be used for any proof.
+        for &(n, m) in &[(0, 0), (0, 2), (8, 0)] {
+            assert_eq!(
+                BulletproofGens::from_bytes(&BulletproofGens::new(n, m).to_bytes()).err(),
+                Some(ProofError::FormatError)
+            );
+        }
+    }
+
+    #[test]
+    fn gens_from_bytes_rejects_malformed_input() {
+        let bytes = BulletproofGens::new(8, 2).to_bytes();
+
+        // Truncated table
+        assert_eq!(
+            BulletproofGens::from_bytes(&bytes[..bytes.len() - 32]).err(),
+            Some(ProofError::FormatError)
+        );
+        assert!(BulletproofGens::from_bytes(&bytes[..8]).is_err());
+
+        // Invalid point encoding
+        let mut corrupted = bytes.clone();
+        corrupted[16 + 5 * 32] ^= 0xff;
+        corrupted[16 + 5 * 32 + 31] = 0xff;
+        assert!(BulletproofGens::from_bytes(&corrupted).is_err());
+
+        // Swap the two parties' G chains: every point is valid, but the
+        // sampled generators no longer match their labels.
+        let mut swapped = bytes.clone();
+        let chain_len = 8 * 32;
+        let (party0, party1) = swapped[16..16 + 2 * chain_len].split_at_mut(chain_len);
+        party0.swap_with_slice(party1);
+        assert!(BulletproofGens::from_bytes(&swapped).is_err());
+    }
 }
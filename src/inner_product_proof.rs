@@ -2,6 +2,8 @@
 #![doc(include = "../docs/inner-product-protocol.md")]
 
 use std::borrow::Borrow;
+use std::convert::TryFrom;
+use std::fmt;
 use std::iter;
 
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
@@ -11,7 +13,12 @@ use merlin::Transcript;
 
 use errors::ProofError;
 use transcript::TranscriptProtocol;
+use util;
 
+/// A proof that \\(c = \langle \mathbf{a}, \mathbf{b} \rangle\\) for
+/// vectors \\(\mathbf{a}, \mathbf{b}\\) committed to in
+/// \\(P = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{b}, \mathbf{H'} \rangle + c \cdot Q\\),
+/// as used in the last step of a [`RangeProof`](::RangeProof).
 #[derive(Clone, Debug)]
 pub struct InnerProductProof {
     pub(crate) L_vec: Vec<CompressedRistretto>,
@@ -181,14 +188,29 @@ impl InnerProductProof {
         }
     }
 
-    /// Computes three vectors of verification scalars \\([u\_{i}^{2}]\\), \\([u\_{i}^{-2}]\\) and \\([s\_{i}]\\) for combined multiscalar multiplication
-    /// in a parent protocol. See [inner product protocol notes](index.html#verification-equation) for details.
-    /// The verifier must provide the input length \\(n\\) explicitly to avoid unbounded allocation within the inner product proof.
-    pub(crate) fn verification_scalars(
+    /// Replays the Fiat-Shamir derivation of the verifier and returns
+    /// the challenges \\(u\_k, \dots, u\_1\\) of the folding rounds in
+    /// creation order, with their inverses.
+    ///
+    /// This allows external verifiers to cross-check the challenge
+    /// sequence; the transcript is left in the same state as after
+    /// [`InnerProductProof::verify`].
+    pub fn fold_challenges(
         &self,
         n: usize,
         transcript: &mut Transcript,
-    ) -> Result<(Vec<Scalar>, Vec<Scalar>, Vec<Scalar>), ProofError> {
+    ) -> Result<Vec<(Scalar, Scalar)>, ProofError> {
+        let (challenges, challenges_inv, _) = self.challenges(n, transcript)?;
+        Ok(challenges.into_iter().zip(challenges_inv).collect())
+    }
+
+    /// Recomputes the challenges \\(u\_k, \dots, u\_1\\) and returns
+    /// them with their inverses and the inverse of their product.
+    fn challenges(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+    ) -> Result<(Vec<Scalar>, Vec<Scalar>, Scalar), ProofError> {
         let lg_n = self.L_vec.len();
         if lg_n >= 32 {
             // 4 billion multiplications should be enough for anyone
@@ -215,6 +237,20 @@ impl InnerProductProof {
         let mut challenges_inv = challenges.clone();
         let allinv = Scalar::batch_invert(&mut challenges_inv);
 
+        Ok((challenges, challenges_inv, allinv))
+    }
+
+    /// Computes three vectors of verification scalars \\([u\_{i}^{2}]\\), \\([u\_{i}^{-2}]\\) and \\([s\_{i}]\\) for combined multiscalar multiplication
+    /// in a parent protocol. See [inner product protocol notes](index.html#verification-equation) for details.
+    /// The verifier must provide the input length \\(n\\) explicitly to avoid unbounded allocation within the inner product proof.
+    pub(crate) fn verification_scalars(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+    ) -> Result<(Vec<Scalar>, Vec<Scalar>, Vec<Scalar>), ProofError> {
+        let lg_n = self.L_vec.len();
+        let (mut challenges, mut challenges_inv, allinv) = self.challenges(n, transcript)?;
+
         // 3. Compute u_i^2 and (1/u_i)^2
 
         for i in 0..lg_n {
@@ -245,7 +281,6 @@ impl InnerProductProof {
     /// but for efficiency the actual protocols would use `verification_scalars`
     /// method to combine inner product verification with other checks
     /// in a single multiscalar multiplication.
-    #[allow(dead_code)]
     pub fn verify<I>(
         &self,
         n: usize,
@@ -278,14 +313,16 @@ impl InnerProductProof {
         let Ls = self
             .L_vec
             .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
-            .collect::<Result<Vec<_>, _>>()?;
+            .map(|p| p.decompress())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| util::invalid_point_error(self.encoded_points()))?;
 
         let Rs = self
             .R_vec
             .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
-            .collect::<Result<Vec<_>, _>>()?;
+            .map(|p| p.decompress())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| util::invalid_point_error(self.encoded_points()))?;
 
         let expect_P = RistrettoPoint::vartime_multiscalar_mul(
             iter::once(self.a * self.b)
@@ -307,6 +344,126 @@ impl InnerProductProof {
         }
     }
 
+    /// Verifies the proof as [`InnerProductProof::verify`] does, but
+    /// keeps the verification scalars in stack buffers when
+    /// \\(n \leq 64\\), falling back to `verify` for larger proofs.
+    ///
+    /// The inverses of the challenges are computed with a single
+    /// inversion, as in `Scalar::batch_invert`, but without its
+    /// scratch allocation, and the points \\(L\_j, R\_j\\) are
+    /// decompressed as they are consumed.
+    pub fn verify_fast<I>(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+        Hprime_factors: I,
+        P: &RistrettoPoint,
+        Q: &RistrettoPoint,
+        G: &[RistrettoPoint],
+        H: &[RistrettoPoint],
+    ) -> Result<(), ProofError>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+    {
+        const MAX_LG_N: usize = 6;
+        const MAX_N: usize = 1 << MAX_LG_N;
+
+        if n > MAX_N {
+            return self.verify(n, transcript, Hprime_factors, P, Q, G, H);
+        }
+        let lg_n = self.L_vec.len();
+        if lg_n > MAX_LG_N || n != (1 << lg_n) {
+            return Err(ProofError::VerificationError);
+        }
+
+        transcript.innerproduct_domain_sep(n as u64);
+
+        // Recompute u_k,...,u_1, keeping the products u_k...u_{i+1}
+        // of the preceding challenges in u_inv_sq.
+        let mut u_sq = [Scalar::zero(); MAX_LG_N];
+        let mut u_inv_sq = [Scalar::zero(); MAX_LG_N];
+        let mut product = Scalar::one();
+        for (i, (L, R)) in self.L_vec.iter().zip(self.R_vec.iter()).enumerate() {
+            transcript.commit_point(b"L", L);
+            transcript.commit_point(b"R", R);
+            let u = transcript.challenge_scalar(b"u");
+            u_sq[i] = u;
+            u_inv_sq[i] = product;
+            product *= u;
+        }
+        let allinv = product.invert();
+
+        // Walk back through the products to invert each challenge,
+        // then square the challenges and their inverses.
+        let mut inv = allinv;
+        for i in (0..lg_n).rev() {
+            let u_inv = inv * u_inv_sq[i];
+            inv *= u_sq[i];
+            u_sq[i] = u_sq[i] * u_sq[i];
+            u_inv_sq[i] = u_inv * u_inv;
+        }
+        let u_sq = &u_sq[..lg_n];
+        let u_inv_sq = &u_inv_sq[..lg_n];
+
+        let mut s = [Scalar::zero(); MAX_N];
+        s[0] = allinv;
+        for i in 1..n {
+            let lg_i = (32 - 1 - (i as u32).leading_zeros()) as usize;
+            let k = 1 << lg_i;
+            s[i] = s[i - k] * u_sq[(lg_n - 1) - lg_i];
+        }
+        let s = &s[..n];
+
+        let a_times_s = s.iter().map(|s_i| self.a * s_i).take(G.len());
+        let h_times_b_div_s = Hprime_factors
+            .into_iter()
+            .zip(s.iter().rev())
+            .map(|(h_i, s_i_inv)| (self.b * s_i_inv) * h_i.borrow());
+
+        let expect_P = RistrettoPoint::optional_multiscalar_mul(
+            iter::once(self.a * self.b)
+                .chain(a_times_s)
+                .chain(h_times_b_div_s)
+                .chain(u_sq.iter().map(|ui| -ui))
+                .chain(u_inv_sq.iter().map(|ui| -ui)),
+            iter::once(Some(*Q))
+                .chain(G.iter().map(|G_i| Some(*G_i)))
+                .chain(H.iter().map(|H_i| Some(*H_i)))
+                .chain(self.L_vec.iter().map(|L| L.decompress()))
+                .chain(self.R_vec.iter().map(|R| R.decompress())),
+        )
+        .ok_or_else(|| util::invalid_point_error(self.encoded_points()))?;
+
+        if expect_P == *P {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Returns the number of folding rounds of the proof,
+    /// \\(\lg n\\) for vectors of length \\(n\\).
+    pub fn depth(&self) -> usize {
+        self.L_vec.len()
+    }
+
+    /// Returns the length \\(n = 2^{\mathrm{depth}}\\) of the vectors
+    /// the proof was created for.
+    pub fn dimension(&self) -> usize {
+        1 << self.depth()
+    }
+
+    /// Returns the size in bytes of the serialization of an inner
+    /// product proof for vectors of length `n`, without creating it.
+    ///
+    /// Vectors are padded to a power of two, so the proof size is
+    /// \\(32 \cdot (2\lceil \lg n \rceil+2)\\) bytes.
+    pub fn size_in_bytes(n: usize) -> usize {
+        let lg_n = n.next_power_of_two().trailing_zeros() as usize;
+        (lg_n * 2 + 2) * 32
+    }
+
     /// Returns the size in bytes required to serialize the inner
     /// product proof.
     ///
@@ -316,6 +473,24 @@ impl InnerProductProof {
         (self.L_vec.len() * 2 + 2) * 32
     }
 
+    /// Returns the points \\(L\_j, R\_j\\) with their offsets in the
+    /// encoding, field names and indices, for
+    /// [`util::invalid_point_error`].
+    pub(crate) fn encoded_points(&self) -> impl Iterator<Item = util::EncodedPoint<'_>> {
+        self.L_vec
+            .iter()
+            .zip(self.R_vec.iter())
+            .enumerate()
+            .flat_map(|(i, (L, R))| {
+                iter::once((2 * i * 32, "L", Some(i), L)).chain(iter::once((
+                    (2 * i + 1) * 32,
+                    "R",
+                    Some(i),
+                    R,
+                )))
+            })
+    }
+
     /// Serializes the proof into a byte array of \\(2n+2\\) 32-byte elements.
     /// The layout of the inner product proof is:
     /// * \\(n\\) pairs of compressed Ristretto points \\(L_0, R_0 \dots, L_{n-1}, R_{n-1}\\),
@@ -335,8 +510,11 @@ impl InnerProductProof {
     /// Returns an error in the following cases:
     /// * the slice does not have \\(2n+2\\) 32-byte elements,
     /// * \\(n\\) is larger or equal to 32 (proof is too big),
-    /// * any of \\(2n\\) points are not valid compressed Ristretto points,
     /// * any of 2 scalars are not canonical scalars modulo Ristretto group order.
+    ///
+    /// The points are decompressed by verification, which returns
+    /// [`ProofError::InvalidPoint`] with the offset of a point that is
+    /// not a valid compressed Ristretto point.
     pub fn from_bytes(slice: &[u8]) -> Result<InnerProductProof, ProofError> {
         let b = slice.len();
         if b % 32 != 0 {
@@ -354,14 +532,14 @@ impl InnerProductProof {
             return Err(ProofError::FormatError);
         }
 
-        use util::read32;
+        use util::{read32, read_point};
 
         let mut L_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
         let mut R_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
         for i in 0..lg_n {
             let pos = 2 * i * 32;
-            L_vec.push(CompressedRistretto(read32(&slice[pos..])));
-            R_vec.push(CompressedRistretto(read32(&slice[pos + 32..])));
+            L_vec.push(read_point(slice, pos));
+            R_vec.push(read_point(slice, pos + 32));
         }
 
         let pos = 2 * lg_n * 32;
@@ -374,6 +552,43 @@ impl InnerProductProof {
     }
 }
 
+impl<'a> TryFrom<&'a [u8]> for InnerProductProof {
+    type Error = ProofError;
+
+    fn try_from(slice: &'a [u8]) -> Result<InnerProductProof, ProofError> {
+        InnerProductProof::from_bytes(slice)
+    }
+}
+
+impl From<InnerProductProof> for Vec<u8> {
+    fn from(proof: InnerProductProof) -> Vec<u8> {
+        proof.to_bytes()
+    }
+}
+
+/// Formats the proof as the lower-case hex encoding of
+/// [`InnerProductProof::to_bytes`].
+impl fmt::LowerHex for InnerProductProof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        util::write_hex(f, &self.to_bytes(), false)
+    }
+}
+
+/// Formats the proof as the upper-case hex encoding of
+/// [`InnerProductProof::to_bytes`].
+impl fmt::UpperHex for InnerProductProof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        util::write_hex(f, &self.to_bytes(), true)
+    }
+}
+
+/// Formats the proof in the same way as [`fmt::LowerHex`].
+impl fmt::Display for InnerProductProof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
 /// Computes an inner product of two vectors
 /// \\[
 ///    {\langle {\mathbf{a}}, {\mathbf{b}} \rangle} = \sum\_{i=0}^{n-1} a\_i \cdot b\_i.
@@ -455,6 +670,53 @@ mod tests {
             )
             .is_ok());
 
+        let mut verifier = Transcript::new(b"innerproducttest");
+        assert!(proof
+            .verify_fast(
+                n,
+                &mut verifier,
+                util::exp_iter(y_inv).take(n),
+                &P,
+                &Q,
+                &G,
+                &H
+            )
+            .is_ok());
+        let mut verifier = Transcript::new(b"innerproducttest");
+        assert!(proof
+            .verify_fast(
+                n,
+                &mut verifier,
+                util::exp_iter(y_inv).take(n),
+                &(P + Q),
+                &Q,
+                &G,
+                &H
+            )
+            .is_err());
+
+        assert_eq!(proof.to_bytes().len(), InnerProductProof::size_in_bytes(n));
+        assert_eq!(proof.dimension(), n);
+        assert_eq!(1 << proof.depth(), n);
+
+        let challenges = proof
+            .fold_challenges(n, &mut Transcript::new(b"innerproducttest"))
+            .unwrap();
+        let (u_sq, u_inv_sq, _) = proof
+            .verification_scalars(n, &mut Transcript::new(b"innerproducttest"))
+            .unwrap();
+        assert_eq!(challenges.len(), u_sq.len());
+        for ((u, u_inv), (u_sq_i, u_inv_sq_i)) in challenges.iter().zip(u_sq.iter().zip(&u_inv_sq))
+        {
+            assert_eq!(u * u_inv, Scalar::one());
+            assert_eq!(u * u, *u_sq_i);
+            assert_eq!(u_inv * u_inv, *u_inv_sq_i);
+        }
+        assert_eq!(
+            format!("{:X}", proof),
+            format!("{:x}", proof).to_uppercase()
+        );
+        assert_eq!(format!("{}", proof).len(), 2 * proof.serialized_size());
         let proof = InnerProductProof::from_bytes(proof.to_bytes().as_slice()).unwrap();
         let mut verifier = Transcript::new(b"innerproducttest");
         assert!(proof
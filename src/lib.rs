@@ -1,6 +1,7 @@
 #![feature(nll)]
 #![feature(external_doc)]
 #![feature(try_trait)]
+#![feature(try_from)]
 #![deny(missing_docs)]
 #![doc(include = "../README.md")]
 #![doc(html_logo_url = "https://doc.dalek.rs/assets/dalek-logo-clear.png")]
@@ -19,7 +20,7 @@ extern crate subtle;
 extern crate serde_derive;
 extern crate serde;
 
-#[macro_use]
+#[cfg(test)]
 extern crate failure;
 
 #[cfg(test)]
@@ -40,20 +41,40 @@ mod notes {
 mod errors;
 mod generators;
 mod inner_product_proof;
+mod proof_system;
 mod range_proof;
+mod scalar_point_inner_product_proof;
 mod transcript;
+mod vector_commitment;
+mod vector_inner_product_proof;
+mod verification_equation;
 
-pub use errors::ProofError;
+pub use errors::{ProofError, ProofIoError};
 pub use generators::{BulletproofGens, BulletproofGensShare, PedersenGens};
-pub use range_proof::RangeProof;
+pub use inner_product_proof::InnerProductProof;
+pub use proof_system::ProofSystem;
+#[cfg(feature = "timing")]
+pub use range_proof::ProofTimings;
+pub use range_proof::{RangeProof, RangeProofWithCommitments, RANGE_PROOF_MAX_BITS};
+pub use scalar_point_inner_product_proof::ScalarPointInnerProductProof;
+pub use transcript::TranscriptProtocol;
+pub use vector_commitment::{OpeningProof, PedersenVectorCommitment};
+pub use vector_inner_product_proof::VectorInnerProductProof;
+pub use verification_equation::VerificationEquation;
 
 #[doc(include = "../docs/aggregation-api.md")]
 pub mod range_proof_mpc {
     pub use errors::MPCError;
+    pub use range_proof::blinding;
     pub use range_proof::dealer;
     pub use range_proof::messages;
     pub use range_proof::party;
 }
 
+/// Helpers for arithmetic on scalars modulo the group order.
+pub mod scalar {
+    pub use util::{is_quadratic_residue, scalar_sqrt};
+}
+
 #[cfg(feature = "yoloproofs")]
 pub mod r1cs;
@@ -0,0 +1,34 @@
+//! Defines a `ProofSystem` trait abstracting over proof backends.
+
+use merlin::Transcript;
+
+use errors::ProofError;
+
+/// A common interface for creating and verifying proofs, so that
+/// protocol code can be written generically over the proof system.
+///
+/// Both methods take a [`Transcript`], so that proofs can be bound to
+/// the rest of a protocol; the verifier must pass a transcript in the
+/// same state as the prover's.
+pub trait ProofSystem {
+    /// The proof created by [`ProofSystem::prove`].
+    type Proof;
+    /// The secret data known only to the prover.
+    type Witness;
+    /// The parameters and statement shared by the prover and verifier.
+    type PublicInput;
+
+    /// Creates a proof for the statement `public` using `witness`.
+    fn prove(
+        witness: Self::Witness,
+        public: &Self::PublicInput,
+        transcript: &mut Transcript,
+    ) -> Result<Self::Proof, ProofError>;
+
+    /// Verifies `proof` for the statement `public`.
+    fn verify(
+        proof: &Self::Proof,
+        public: &Self::PublicInput,
+        transcript: &mut Transcript,
+    ) -> Result<(), ProofError>;
+}
@@ -0,0 +1,153 @@
+//! Definition of a constraint system wrapper enforcing a limit on
+//! the number of multipliers.
+
+use super::{ConstraintSystem, LinearCombination, R1CSError, Variable};
+use curve25519_dalek::scalar::Scalar;
+use generators::PedersenGens;
+
+/// A [`ConstraintSystem`] that allows at most a given number of
+/// multipliers in the wrapped constraint system, created by
+/// [`ConstraintSystem::with_budget`].
+///
+/// [`ConstraintSystem::allocate`] and
+/// [`ConstraintSystem::assert_nonzero`] return
+/// [`R1CSError::BudgetExceeded`] instead of allocating a multiplier
+/// past the budget, as do [`BudgetedCS::try_multiply`] and
+/// [`BudgetedCS::try_merge_fork`].  Since [`ConstraintSystem::multiply`]
+/// and [`ConstraintSystem::merge_fork`] cannot fail, they always
+/// allocate, and the overrun is reported by [`BudgetedCS::finish`],
+/// which must be checked before the proof is created or verified.
+///
+/// The budget counts all multipliers of the wrapped constraint system,
+/// including ones allocated before wrapping it and ones allocated in
+/// a merged fork.
+pub struct BudgetedCS<'a, CS: 'a + ConstraintSystem> {
+    cs: &'a mut CS,
+    budget: usize,
+}
+
+impl<'a, CS: ConstraintSystem> BudgetedCS<'a, CS> {
+    /// Wraps `cs`, allowing at most `budget` multipliers.
+    pub fn new(cs: &'a mut CS, budget: usize) -> Self {
+        BudgetedCS { cs, budget }
+    }
+
+    /// Returns the number of multipliers that can still be allocated.
+    pub fn remaining(&self) -> usize {
+        self.budget.saturating_sub(self.cs.multipliers_len())
+    }
+
+    /// Behaves as [`ConstraintSystem::multiply`], but returns
+    /// [`R1CSError::BudgetExceeded`] instead of allocating a
+    /// multiplier past the budget.
+    pub fn try_multiply(
+        &mut self,
+        left: LinearCombination,
+        right: LinearCombination,
+    ) -> Result<(Variable, Variable, Variable), R1CSError> {
+        self.check_next()?;
+        Ok(self.cs.multiply(left, right))
+    }
+
+    /// Behaves as [`ConstraintSystem::merge_fork`], but returns
+    /// [`R1CSError::BudgetExceeded`] and drops `fork` instead of
+    /// merging it if its multipliers exceed the budget.
+    pub fn try_merge_fork(&mut self, fork: CS::Fork) -> Result<(), R1CSError> {
+        let used = fork.multipliers_len();
+        if used > self.budget {
+            return Err(R1CSError::BudgetExceeded {
+                used,
+                budget: self.budget,
+            });
+        }
+        self.cs.merge_fork(fork);
+        Ok(())
+    }
+
+    /// Releases the wrapped constraint system, returning
+    /// [`R1CSError::BudgetExceeded`] if it has more multipliers than
+    /// the budget allows.
+    pub fn finish(self) -> Result<(), R1CSError> {
+        self.check(0)
+    }
+
+    fn check_next(&self) -> Result<(), R1CSError> {
+        self.check(1)
+    }
+
+    /// Checks that `extra` more multipliers fit in the budget.
+    fn check(&self, extra: usize) -> Result<(), R1CSError> {
+        let used = self.cs.multipliers_len() + extra;
+        if used > self.budget {
+            return Err(R1CSError::BudgetExceeded {
+                used,
+                budget: self.budget,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<'a, CS: ConstraintSystem> ConstraintSystem for BudgetedCS<'a, CS> {
+    type Fork = CS::Fork;
+
+    fn multiply(
+        &mut self,
+        left: LinearCombination,
+        right: LinearCombination,
+    ) -> (Variable, Variable, Variable) {
+        self.cs.multiply(left, right)
+    }
+
+    fn allocate<F>(&mut self, assign_fn: F) -> Result<(Variable, Variable, Variable), R1CSError>
+    where
+        F: FnOnce() -> Result<(Scalar, Scalar, Scalar), R1CSError>,
+    {
+        self.check_next()?;
+        self.cs.allocate(assign_fn)
+    }
+
+    fn constrain(&mut self, lc: LinearCombination) {
+        self.cs.constrain(lc)
+    }
+
+    fn assert_nonzero(&mut self, var: Variable) -> Result<(), R1CSError> {
+        self.check_next()?;
+        self.cs.assert_nonzero(var)
+    }
+
+    fn committed_len(&self) -> usize {
+        self.cs.committed_len()
+    }
+
+    fn multipliers_len(&self) -> usize {
+        self.cs.multipliers_len()
+    }
+
+    fn constraints_len(&self) -> usize {
+        self.cs.constraints_len()
+    }
+
+    fn total_verification_cost(&self) -> usize {
+        self.cs.total_verification_cost()
+    }
+
+    /// Forks the wrapped constraint system.  The fork itself is not
+    /// budgeted, but its multipliers count against the budget once
+    /// it is merged.
+    fn fork(&self) -> CS::Fork {
+        self.cs.fork()
+    }
+
+    fn merge_fork(&mut self, fork: CS::Fork) {
+        self.cs.merge_fork(fork)
+    }
+
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        self.cs.challenge_scalar(label)
+    }
+
+    fn pedersen_gens(&self) -> &PedersenGens {
+        self.cs.pedersen_gens()
+    }
+}
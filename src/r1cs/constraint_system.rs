@@ -1,7 +1,10 @@
 //! Definition of the constraint system trait.
 
-use super::{LinearCombination, R1CSError, Variable};
+use super::{BudgetedCS, LinearCombination, R1CSError, Variable};
 use curve25519_dalek::scalar::Scalar;
+use generators::PedersenGens;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// The interface for a constraint system, abstracting over the prover
 /// and verifier's roles.
@@ -16,6 +19,10 @@ use curve25519_dalek::scalar::Scalar;
 /// using the `ConstraintSystem` trait, so that the prover and
 /// verifier share the logic for specifying constraints.
 pub trait ConstraintSystem {
+    /// The type of an independent copy of the constraint system,
+    /// created by [`ConstraintSystem::fork`].
+    type Fork: ConstraintSystem<Fork = Self::Fork>;
+
     /// Allocate and constrain multiplication variables.
     ///
     /// Allocate variables `left`, `right`, and `out`
@@ -53,6 +60,63 @@ pub trait ConstraintSystem {
     /// ```
     fn constrain(&mut self, lc: LinearCombination);
 
+    /// Enforce the constraint that
+    /// ```text
+    /// var != 0
+    /// ```
+    /// by allocating a multiplier for `var * inv = 1`, where the
+    /// prover assigns `inv` the inverse of the value of `var`.
+    ///
+    /// The prover returns [`R1CSError::WitnessIsZero`] if `var` is
+    /// assigned zero, since no witness can satisfy the constraint.
+    fn assert_nonzero(&mut self, var: Variable) -> Result<(), R1CSError>;
+
+    /// Returns the number of externally committed (high-level)
+    /// variables of the constraint system.
+    ///
+    /// These are the only inputs to the constraint system: their
+    /// values are private to the prover, while their commitments are
+    /// known to the verifier.
+    fn committed_len(&self) -> usize;
+
+    /// Returns the number of multiplication gates allocated so far.
+    fn multipliers_len(&self) -> usize;
+
+    /// Returns the number of explicit constraints added so far,
+    /// including the ones added by [`ConstraintSystem::multiply`].
+    fn constraints_len(&self) -> usize;
+
+    /// Returns the sum of the [`LinearCombination::cost`] of all
+    /// explicit constraints added so far.
+    ///
+    /// This is a rough estimate of the work the verifier spends on
+    /// the constraints, to help minimize verification time when
+    /// designing circuits.  It does not account for the savings from
+    /// batching the scalar multiplications into a single
+    /// multiscalar multiplication.
+    fn total_verification_cost(&self) -> usize;
+
+    /// Creates an independent copy of the constraint system in its
+    /// current state, including its variables, constraints and
+    /// transcript state.  Changes to the fork do not affect `self`.
+    ///
+    /// The fork can either be dropped, discarding the changes made to
+    /// it, or passed to [`ConstraintSystem::merge_fork`] to adopt them.
+    ///
+    /// Note that the prover and verifier must still build the same
+    /// constraints, so the choice of whether to merge a fork must not
+    /// depend on secret witness data.
+    fn fork(&self) -> Self::Fork;
+
+    /// Replaces the state of the constraint system with that of
+    /// `fork`, adopting the variables, constraints and transcript
+    /// state added to the fork.
+    ///
+    /// Panics if `fork` was not created from `self`, or if `self` has
+    /// been modified since the fork was created, including by
+    /// drawing a challenge with [`ConstraintSystem::challenge_scalar`].
+    fn merge_fork(&mut self, fork: Self::Fork);
+
     /// Obtain a challenge scalar bound to the assignments of all of
     /// the externally committed wires.
     ///
@@ -68,4 +132,88 @@ pub trait ConstraintSystem {
     /// that the constraints are sound, it is **also** the user's
     /// responsibility to ensure that each challenge circuit is sound.
     fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+
+    /// Returns the Pedersen generators of the commitments to the
+    /// externally committed variables.
+    ///
+    /// Gadgets that need the generators, e.g. to compute a commitment
+    /// outside of the constraint system, can take them from here
+    /// instead of having them passed alongside the constraint system.
+    fn pedersen_gens(&self) -> &PedersenGens;
+
+    /// Returns a snapshot of the size of the constraint system, read
+    /// from the counters of the variables and constraints allocated
+    /// so far.
+    fn stats(&self) -> ConstraintSystemStats {
+        ConstraintSystemStats {
+            multiplier_count: self.multipliers_len(),
+            linear_constraint_count: self.constraints_len(),
+            input_variable_count: self.committed_len(),
+            allocated_variable_count: 3 * self.multipliers_len(),
+        }
+    }
+
+    /// Wraps the constraint system in a [`BudgetedCS`] that allows at
+    /// most `max_multipliers` multipliers.
+    fn with_budget(&mut self, max_multipliers: usize) -> BudgetedCS<'_, Self>
+    where
+        Self: Sized,
+    {
+        BudgetedCS::new(self, max_multipliers)
+    }
+}
+
+/// Returns an identifier for the state of a constraint system,
+/// distinct from all identifiers returned before.
+///
+/// Constraint systems take a new identifier whenever they are
+/// modified, and their forks record the identifier of the state they
+/// were created from, so that [`ConstraintSystem::merge_fork`] can
+/// check that the fork was created from the current state.
+pub(super) fn next_state_id() -> usize {
+    static NEXT_STATE_ID: AtomicUsize = AtomicUsize::new(0);
+    NEXT_STATE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A snapshot of the size of a constraint system, returned by
+/// [`ConstraintSystem::stats`].
+///
+/// Comparing the snapshots taken before and after a gadget is
+/// applied gives the size of the gadget.  The `Display` impl formats
+/// the snapshot as a table.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConstraintSystemStats {
+    /// The number of multiplication gates,
+    /// see [`ConstraintSystem::multipliers_len`].
+    pub multiplier_count: usize,
+    /// The number of explicit linear constraints,
+    /// see [`ConstraintSystem::constraints_len`].
+    pub linear_constraint_count: usize,
+    /// The number of externally committed variables,
+    /// see [`ConstraintSystem::committed_len`].
+    pub input_variable_count: usize,
+    /// The number of variables allocated by the multiplication gates,
+    /// three for each of them.
+    pub allocated_variable_count: usize,
+}
+
+impl fmt::Display for ConstraintSystemStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<20} {:>10}", "multipliers", self.multiplier_count)?;
+        writeln!(
+            f,
+            "{:<20} {:>10}",
+            "linear constraints", self.linear_constraint_count
+        )?;
+        writeln!(
+            f,
+            "{:<20} {:>10}",
+            "input variables", self.input_variable_count
+        )?;
+        write!(
+            f,
+            "{:<20} {:>10}",
+            "allocated variables", self.allocated_variable_count
+        )
+    }
 }
@@ -0,0 +1,130 @@
+//! Boolean gadgets over bit-decomposed variables.
+
+use curve25519_dalek::scalar::Scalar;
+use r1cs::{ConstraintSystem, LinearCombination, R1CSError, Variable};
+
+/// Allocates the `n` bits of `value`, least significant first, and
+/// constrains each of them to be 0 or 1.
+///
+/// Returns the bits and the linear combination packing them.
+pub fn allocate_bits<CS: ConstraintSystem>(
+    cs: &mut CS,
+    value: Option<u64>,
+    n: usize,
+) -> Result<(Vec<Variable>, LinearCombination), R1CSError> {
+    let mut bits = Vec::with_capacity(n);
+    let mut packed = LinearCombination::default();
+    let mut exp_2 = Scalar::one();
+    for i in 0..n {
+        // Allocate a * b = 0, where b = 1 - a, so that a is a bit.
+        let (a, b, o) = cs.allocate(|| {
+            let value = value.ok_or(R1CSError::MissingAssignment)?;
+            let bit = Scalar::from((value >> i) & 1);
+            Ok((bit, Scalar::one() - bit, Scalar::zero()))
+        })?;
+        cs.constrain(o.into());
+        cs.constrain(a + b - Scalar::one());
+
+        packed = packed + a * exp_2;
+        exp_2 = exp_2 + exp_2;
+        bits.push(a);
+    }
+    Ok((bits, packed))
+}
+
+/// Decomposes `v` into `n` bits, least significant first, and
+/// constrains each of them to be 0 or 1.
+///
+/// The prover passes the `value` of `v`, and the verifier passes `None`.
+pub fn bit_decompose<CS: ConstraintSystem>(
+    cs: &mut CS,
+    v: Variable,
+    value: Option<u64>,
+    n: usize,
+) -> Result<Vec<Variable>, R1CSError> {
+    let (bits, packed) = allocate_bits(cs, value, n)?;
+    cs.constrain(packed - v);
+    Ok(bits)
+}
+
+/// Returns the linear combination \\(\sum_i 2^i \cdot bits_i\\).
+pub fn pack_bits(bits: Vec<LinearCombination>) -> LinearCombination {
+    let mut packed = LinearCombination::default();
+    let mut exp_2 = Scalar::one();
+    for bit in bits {
+        packed = packed + bit * exp_2;
+        exp_2 = exp_2 + exp_2;
+    }
+    packed
+}
+
+/// Returns `a_i XOR b_i` for each pair of bits, as `a_i + b_i - 2 a_i b_i`.
+///
+/// The bits must already be constrained to be 0 or 1.
+pub fn xor_bits<CS, L>(cs: &mut CS, a_bits: &[L], b_bits: &[L]) -> Vec<LinearCombination>
+where
+    CS: ConstraintSystem,
+    L: Clone + Into<LinearCombination>,
+{
+    assert_eq!(a_bits.len(), b_bits.len());
+    a_bits
+        .iter()
+        .zip(b_bits)
+        .map(|(a, b)| {
+            let (a, b): (LinearCombination, LinearCombination) =
+                (a.clone().into(), b.clone().into());
+            let (_, _, ab) = cs.multiply(a.clone(), b.clone());
+            a + b - ab * Scalar::from(2u64)
+        })
+        .collect()
+}
+
+/// Returns `a_i AND b_i` for each pair of bits, as `a_i b_i`.
+///
+/// The bits must already be constrained to be 0 or 1.
+pub fn and_bits<CS, L>(cs: &mut CS, a_bits: &[L], b_bits: &[L]) -> Vec<LinearCombination>
+where
+    CS: ConstraintSystem,
+    L: Clone + Into<LinearCombination>,
+{
+    assert_eq!(a_bits.len(), b_bits.len());
+    a_bits
+        .iter()
+        .zip(b_bits)
+        .map(|(a, b)| cs.multiply(a.clone().into(), b.clone().into()).2.into())
+        .collect()
+}
+
+/// Returns `a_i OR b_i` for each pair of bits, as `a_i + b_i - a_i b_i`.
+///
+/// The bits must already be constrained to be 0 or 1.
+pub fn or_bits<CS, L>(cs: &mut CS, a_bits: &[L], b_bits: &[L]) -> Vec<LinearCombination>
+where
+    CS: ConstraintSystem,
+    L: Clone + Into<LinearCombination>,
+{
+    assert_eq!(a_bits.len(), b_bits.len());
+    a_bits
+        .iter()
+        .zip(b_bits)
+        .map(|(a, b)| {
+            let (a, b): (LinearCombination, LinearCombination) =
+                (a.clone().into(), b.clone().into());
+            let (_, _, ab) = cs.multiply(a.clone(), b.clone());
+            a + b - ab
+        })
+        .collect()
+}
+
+/// Returns `NOT a_i` for each bit, as `1 - a_i`.
+///
+/// The bits must already be constrained to be 0 or 1.
+pub fn not_bits<L>(a_bits: &[L]) -> Vec<LinearCombination>
+where
+    L: Clone + Into<LinearCombination>,
+{
+    a_bits
+        .iter()
+        .map(|a| LinearCombination::from(Scalar::one()) - a.clone())
+        .collect()
+}
@@ -0,0 +1,15 @@
+//! Reusable gadgets for the constraint system.
+//!
+//! Like the [`ConstraintSystem`](::r1cs::ConstraintSystem) itself, the
+//! gadgets are shared by the prover and the verifier: the prover passes
+//! the values of the witness as `Some`, and the verifier passes `None`.
+
+mod bits;
+
+pub mod modular;
+pub mod rsa;
+pub mod sha256;
+
+pub use self::bits::{
+    allocate_bits, and_bits, bit_decompose, not_bits, or_bits, pack_bits, xor_bits,
+};
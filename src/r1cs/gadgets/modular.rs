@@ -0,0 +1,43 @@
+//! Gadgets for modular arithmetic.
+
+use curve25519_dalek::scalar::Scalar;
+use r1cs::{ConstraintSystem, R1CSError, Variable};
+
+use super::allocate_bits;
+
+/// Returns a variable constrained to `a * b mod 2^n`, for `n <= 64`.
+///
+/// The product is split as `a * b = q * 2^n + r` with `q` and `r`
+/// range-checked to `n` bits, so the result `r` is only correct if the
+/// caller has constrained `a` and `b` to `n` bits as well.  The split
+/// is then unique, since `q * 2^n + r < 2^128` cannot wrap around the
+/// group order.
+///
+/// The prover passes the `values` of `a` and `b`, and the verifier
+/// passes `None`.
+pub fn mul_mod_2n<CS: ConstraintSystem>(
+    cs: &mut CS,
+    a: Variable,
+    b: Variable,
+    values: Option<(u64, u64)>,
+    n: usize,
+) -> Result<Variable, R1CSError> {
+    assert!(n != 0 && n <= 64);
+    let product = values.map(|(a, b)| u128::from(a) * u128::from(b));
+    let q = product.map(|p| (p >> n) as u64);
+    let r = product.map(|p| (p & ((1u128 << n) - 1)) as u64);
+
+    let (_, _, ab) = cs.multiply(a.into(), b.into());
+    let (_, q) = allocate_bits(cs, q, n)?;
+    let (_, r) = allocate_bits(cs, r, n)?;
+
+    let mut exp_2_n = Scalar::one();
+    for _ in 0..n {
+        exp_2_n = exp_2_n + exp_2_n;
+    }
+    cs.constrain(q * exp_2_n + r.clone() - ab);
+
+    // Bind the packed remainder to a single variable.
+    let (_, _, r) = cs.multiply(r, Scalar::one().into());
+    Ok(r)
+}
@@ -0,0 +1,233 @@
+//! A gadget for RSA verification over multi-limb integers.
+
+#![allow(non_snake_case)]
+
+use byteorder::{ByteOrder, LittleEndian};
+use curve25519_dalek::scalar::Scalar;
+use r1cs::{ConstraintSystem, LinearCombination, R1CSError, Variable};
+use std::cmp::Ordering;
+use std::iter;
+
+use super::{allocate_bits, bit_decompose};
+
+/// Returns \\(2^n\\) as a scalar.
+fn exp_2(n: usize) -> Scalar {
+    let mut exp_2 = Scalar::one();
+    for _ in 0..n {
+        exp_2 = exp_2 + exp_2;
+    }
+    exp_2
+}
+
+/// Returns the product of the integers with little-endian limbs `a`
+/// and `b` of `limb_bits` bits, with `a.len() + b.len()` limbs.
+fn mul_limbs(a: &[u64], b: &[u64], limb_bits: usize) -> Vec<u64> {
+    let mask = (1u128 << limb_bits) - 1;
+    let mut product = vec![0u64; a.len() + b.len()];
+    for (i, &a_i) in a.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, &b_j) in b.iter().enumerate() {
+            let t = u128::from(a_i) * u128::from(b_j) + u128::from(product[i + j]) + carry;
+            product[i + j] = (t & mask) as u64;
+            carry = t >> limb_bits;
+        }
+        product[i + b.len()] = carry as u64;
+    }
+    product
+}
+
+/// Returns the quotient and remainder of the integer with limbs `a`
+/// by the integer with limbs `N`, each with `N.len()` limbs, by
+/// binary long division.
+fn divrem_limbs(a: &[u64], N: &[u64], limb_bits: usize) -> (Vec<u64>, Vec<u64>) {
+    let k = N.len();
+    let mask = (1u64 << limb_bits) - 1;
+    let N: Vec<u64> = N.iter().cloned().chain(iter::once(0)).collect();
+    let mut q = vec![0u64; k];
+    // One spare limb, since the remainder is doubled before reducing.
+    let mut r = vec![0u64; k + 1];
+    for bit in (0..a.len() * limb_bits).rev() {
+        let mut carry = (a[bit / limb_bits] >> (bit % limb_bits)) & 1;
+        for r_i in r.iter_mut() {
+            let t = (*r_i << 1) | carry;
+            *r_i = t & mask;
+            carry = t >> limb_bits;
+        }
+        if r.iter().rev().cmp(N.iter().rev()) != Ordering::Less {
+            let mut borrow = 0u64;
+            for (r_i, N_i) in r.iter_mut().zip(N.iter()) {
+                let sub = N_i + borrow;
+                borrow = (*r_i < sub) as u64;
+                *r_i = (*r_i + (borrow << limb_bits) - sub) & mask;
+            }
+            assert!(bit / limb_bits < k, "quotient does not fit in k limbs");
+            q[bit / limb_bits] |= 1 << (bit % limb_bits);
+        }
+    }
+    r.truncate(k);
+    (q, r)
+}
+
+/// Returns the limbs of `a * b mod N`, where `a` and `b` are given by
+/// linear combinations of their limbs, each constrained to
+/// `limb_bits` bits.
+///
+/// The prover allocates the quotient `q` and remainder `r` of
+/// `a * b = q * N + r`, constrained to `limb_bits` bits per limb, and
+/// the equation is checked limb by limb.  The carries between the
+/// limbs are allocated and range-checked, so that no limb equation
+/// can wrap around the group order; the equations then hold over the
+/// integers, and `r` is congruent to `a * b` modulo `N`.
+///
+/// The prover passes the `values` of the limbs of `a` and `b`, and
+/// the verifier passes `None`.
+fn mul_mod<CS: ConstraintSystem>(
+    cs: &mut CS,
+    a: &[LinearCombination],
+    b: &[LinearCombination],
+    values: Option<(&[u64], &[u64])>,
+    N: &[u64],
+    limb_bits: usize,
+) -> Result<(Vec<LinearCombination>, Option<Vec<u64>>), R1CSError> {
+    let k = N.len();
+    let qr_values = values.map(|(a, b)| divrem_limbs(&mul_limbs(a, b, limb_bits), N, limb_bits));
+
+    let mut q = Vec::with_capacity(k);
+    let mut r = Vec::with_capacity(k);
+    for i in 0..k {
+        q.push(allocate_bits(cs, qr_values.as_ref().map(|(q, _)| q[i]), limb_bits)?.1);
+        r.push(allocate_bits(cs, qr_values.as_ref().map(|(_, r)| r[i]), limb_bits)?.1);
+    }
+
+    // z_t = sum_{i+j=t} (a_i b_j - q_i N_j) - r_t, with its value.
+    let mut z = vec![LinearCombination::default(); 2 * k - 1];
+    let mut z_values = vec![0i128; 2 * k - 1];
+    for i in 0..k {
+        for j in 0..k {
+            let (_, _, ab) = cs.multiply(a[i].clone(), b[j].clone());
+            z[i + j] = z[i + j].clone() + ab - q[i].clone() * Scalar::from(N[j]);
+        }
+    }
+    for t in 0..k {
+        z[t] = z[t].clone() - r[t].clone();
+    }
+    if let (Some((a, b)), Some((q, r))) = (values, qr_values.as_ref()) {
+        for i in 0..k {
+            for j in 0..k {
+                z_values[i + j] += i128::from(a[i]) * i128::from(b[j]);
+                z_values[i + j] -= i128::from(q[i]) * i128::from(N[j]);
+            }
+            z_values[i] -= i128::from(r[i]);
+        }
+    }
+
+    // Each carry c_t satisfies |c_t| < 2k * 2^limb_bits, so it is
+    // range-checked with an offset to make it non-negative.
+    let k_bits = 64 - (k as u64).leading_zeros() as usize;
+    let carry_bits = limb_bits + k_bits + 2;
+    let offset = 1i128 << (carry_bits - 1);
+
+    let mut carry = LinearCombination::default();
+    let mut carry_value = 0i128;
+    for t in 0..2 * k - 1 {
+        let sum = z[t].clone() + carry;
+        if t == 2 * k - 2 {
+            cs.constrain(sum);
+            break;
+        }
+        carry_value = (z_values[t] + carry_value) >> limb_bits;
+        let (_, shifted) = allocate_bits(
+            cs,
+            values.map(|_| (carry_value + offset) as u64),
+            carry_bits,
+        )?;
+        carry = shifted - Scalar::from(offset as u64);
+        cs.constrain(sum - carry.clone() * exp_2(limb_bits));
+    }
+
+    Ok((r, qr_values.map(|(_, r)| r)))
+}
+
+/// Constrains `x^e = y mod N`, where `x` is given by the variables of
+/// its `N.len()` little-endian limbs of `limb_bits` bits, and `y` and
+/// `N` are public, given by their limbs in the same representation.
+/// The limbs of `x` are range-checked by the gadget.
+///
+/// Uses square-and-multiply over the bits of `e`: each bit after the
+/// most significant one costs a modular squaring, and each of those
+/// bits that is set costs a further modular multiplication.
+///
+/// The prover passes the limbs of the value of `x`, and the verifier
+/// passes `None`.
+///
+/// # Panics
+///
+/// Panics if a limb of `y` or `N` does not fit in `limb_bits` bits.
+pub fn rsa_verify<CS: ConstraintSystem>(
+    cs: &mut CS,
+    x: &[Variable],
+    x_value: Option<&[u64]>,
+    y: &[Scalar],
+    e: u64,
+    N: &[Scalar],
+    limb_bits: usize,
+) -> Result<(), R1CSError> {
+    let k = N.len();
+    assert!(e >= 1);
+    assert!(limb_bits >= 2);
+    assert!(limb_bits <= 32);
+    assert_eq!(x.len(), k);
+    assert_eq!(y.len(), k);
+    for y_i in y {
+        to_limb(y_i, limb_bits);
+    }
+    let N: Vec<u64> = N.iter().map(|N_i| to_limb(N_i, limb_bits)).collect();
+
+    let mut base = Vec::with_capacity(k);
+    for (i, &x_i) in x.iter().enumerate() {
+        bit_decompose(cs, x_i, x_value.map(|x| x[i]), limb_bits)?;
+        base.push(LinearCombination::from(x_i));
+    }
+
+    let mut acc = base.clone();
+    let mut acc_value = x_value.map(|x| x.to_vec());
+    for i in (0..63 - e.leading_zeros() as usize).rev() {
+        let values = acc_value.as_ref().map(|a| (&a[..], &a[..]));
+        let (sq, sq_value) = mul_mod(cs, &acc, &acc, values, &N, limb_bits)?;
+        acc = sq;
+        acc_value = sq_value;
+        if (e >> i) & 1 == 1 {
+            let values = match (acc_value.as_ref(), x_value) {
+                (Some(a), Some(x)) => Some((&a[..], x)),
+                _ => None,
+            };
+            let (mul, mul_value) = mul_mod(cs, &acc, &base, values, &N, limb_bits)?;
+            acc = mul;
+            acc_value = mul_value;
+        }
+    }
+
+    for (acc_i, &y_i) in acc.into_iter().zip(y) {
+        cs.constrain(acc_i - y_i);
+    }
+    Ok(())
+}
+
+/// Returns the limb `limb` as an integer.
+///
+/// # Panics
+///
+/// Panics if `limb` does not fit in `limb_bits` bits.
+fn to_limb(limb: &Scalar, limb_bits: usize) -> u64 {
+    let bytes = limb.as_bytes();
+    assert!(
+        bytes[8..].iter().all(|&b| b == 0),
+        "limb does not fit in limb_bits bits"
+    );
+    let value = LittleEndian::read_u64(&bytes[..8]);
+    assert!(
+        value >> limb_bits == 0,
+        "limb does not fit in limb_bits bits"
+    );
+    value
+}
@@ -0,0 +1,282 @@
+//! A gadget for the SHA-256 hash function.
+
+#![allow(non_snake_case)]
+
+use curve25519_dalek::scalar::Scalar;
+use r1cs::{ConstraintSystem, LinearCombination, R1CSError, Variable};
+
+use super::{allocate_bits, pack_bits, xor_bits};
+
+/// The SHA-256 round constants.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The SHA-256 initial hash value.
+const H: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// A 32-bit word as linear combinations of its bits, least
+/// significant first, each constrained to be 0 or 1.
+///
+/// The prover also keeps the `value` of the word, since it is needed
+/// to allocate the bits of sums; the verifier has `None`.
+#[derive(Clone)]
+struct Word {
+    bits: Vec<LinearCombination>,
+    value: Option<u32>,
+}
+
+impl Word {
+    /// Returns the public word `value`, which costs no multipliers.
+    fn constant(value: u32) -> Word {
+        Word {
+            bits: (0..32)
+                .map(|i| Scalar::from((value >> i) & 1).into())
+                .collect(),
+            value: Some(value),
+        }
+    }
+
+    /// Returns the word of the bits `bits`, most significant first,
+    /// as they appear in the message.
+    fn from_be_bits(bits: &[Variable], value: Option<u32>) -> Word {
+        assert_eq!(bits.len(), 32);
+        Word {
+            bits: bits.iter().rev().map(|&b| b.into()).collect(),
+            value,
+        }
+    }
+
+    fn rotr(&self, n: usize) -> Word {
+        Word {
+            bits: (0..32).map(|i| self.bits[(i + n) % 32].clone()).collect(),
+            value: self.value.map(|v| v.rotate_right(n as u32)),
+        }
+    }
+
+    fn shr(&self, n: usize) -> Word {
+        Word {
+            bits: (0..32)
+                .map(|i| match self.bits.get(i + n) {
+                    Some(bit) => bit.clone(),
+                    None => LinearCombination::default(),
+                })
+                .collect(),
+            value: self.value.map(|v| v >> n),
+        }
+    }
+
+    /// Returns the linear combination \\(\sum_i 2^i \cdot bits_i\\).
+    fn pack(&self) -> LinearCombination {
+        pack_bits(self.bits.clone())
+    }
+}
+
+/// The bits of a word allocated as variables, least significant
+/// first, with the value of the word if known to the prover.
+type WordBits = (Vec<Variable>, Option<u32>);
+
+/// Returns `a XOR b`.
+fn xor<CS: ConstraintSystem>(cs: &mut CS, a: &Word, b: &Word) -> Word {
+    Word {
+        bits: xor_bits(cs, &a.bits, &b.bits),
+        value: a.value.and_then(|a| b.value.map(|b| a ^ b)),
+    }
+}
+
+/// Returns `(e AND f) XOR (NOT e AND g)`, as `g_i + e_i (f_i - g_i)`
+/// for each bit.
+fn ch<CS: ConstraintSystem>(cs: &mut CS, e: &Word, f: &Word, g: &Word) -> Word {
+    let bits = (0..32)
+        .map(|i| {
+            let (_, _, o) = cs.multiply(e.bits[i].clone(), f.bits[i].clone() - g.bits[i].clone());
+            g.bits[i].clone() + o
+        })
+        .collect();
+    let value = match (e.value, f.value, g.value) {
+        (Some(e), Some(f), Some(g)) => Some((e & f) ^ (!e & g)),
+        _ => None,
+    };
+    Word { bits, value }
+}
+
+/// Returns the bitwise majority of `a`, `b` and `c`, as
+/// `t_i + a_i (b_i + c_i - 2 t_i)` with `t_i = b_i c_i` for each bit.
+fn maj<CS: ConstraintSystem>(cs: &mut CS, a: &Word, b: &Word, c: &Word) -> Word {
+    let bits = (0..32)
+        .map(|i| {
+            let (_, _, t) = cs.multiply(b.bits[i].clone(), c.bits[i].clone());
+            let (_, _, o) = cs.multiply(
+                a.bits[i].clone(),
+                b.bits[i].clone() + c.bits[i].clone() - t * Scalar::from(2u64),
+            );
+            t + o
+        })
+        .collect();
+    let value = match (a.value, b.value, c.value) {
+        (Some(a), Some(b), Some(c)) => Some((a & b) ^ (a & c) ^ (b & c)),
+        _ => None,
+    };
+    Word { bits, value }
+}
+
+/// Returns the bits of the sum of `words` modulo \\(2^{32}\\), least
+/// significant first.
+///
+/// The sum is split into the 32-bit result and a carry, both
+/// allocated as bits, so the split is unique as long as the sum of
+/// the packed words cannot wrap around the group order.
+fn add_bits<CS: ConstraintSystem>(cs: &mut CS, words: &[Word]) -> Result<WordBits, R1CSError> {
+    let sum = words
+        .iter()
+        .try_fold(0u64, |sum, word| word.value.map(|v| sum + u64::from(v)));
+    let carry_bits = 64 - (words.len() as u64 - 1).leading_zeros() as usize;
+
+    let (bits, result) = allocate_bits(cs, sum.map(|s| s & 0xffff_ffff), 32)?;
+    let (_, carry) = allocate_bits(cs, sum.map(|s| s >> 32), carry_bits)?;
+    let packed = words
+        .iter()
+        .fold(LinearCombination::default(), |lc, word| lc + word.pack());
+    cs.constrain(result + carry * Scalar::from(1u64 << 32) - packed);
+
+    Ok((bits, sum.map(|s| s as u32)))
+}
+
+/// Returns the sum of `words` modulo \\(2^{32}\\).
+fn add<CS: ConstraintSystem>(cs: &mut CS, words: &[Word]) -> Result<Word, R1CSError> {
+    let (bits, value) = add_bits(cs, words)?;
+    Ok(Word {
+        bits: bits.into_iter().map(|b| b.into()).collect(),
+        value,
+    })
+}
+
+/// Constrains the output of the SHA-256 compression function applied
+/// to the 16 words of `block` and the hash value `state`, whose words
+/// must already be constrained to bits.
+///
+/// Returns the bits of the words of the new hash value, least
+/// significant first.
+fn compress<CS: ConstraintSystem>(
+    cs: &mut CS,
+    state: &[Word],
+    block: &[Word],
+) -> Result<Vec<WordBits>, R1CSError> {
+    let mut w = block.to_vec();
+    for t in 16..64 {
+        let s0 = xor(cs, &w[t - 15].rotr(7), &w[t - 15].rotr(18));
+        let s0 = xor(cs, &s0, &w[t - 15].shr(3));
+        let s1 = xor(cs, &w[t - 2].rotr(17), &w[t - 2].rotr(19));
+        let s1 = xor(cs, &s1, &w[t - 2].shr(10));
+        let w_t = add(cs, &[s1, w[t - 7].clone(), s0, w[t - 16].clone()])?;
+        w.push(w_t);
+    }
+
+    let mut v = state.to_vec();
+    for t in 0..64 {
+        let S1 = xor(cs, &v[4].rotr(6), &v[4].rotr(11));
+        let S1 = xor(cs, &S1, &v[4].rotr(25));
+        let ch = ch(cs, &v[4], &v[5], &v[6]);
+        let S0 = xor(cs, &v[0].rotr(2), &v[0].rotr(13));
+        let S0 = xor(cs, &S0, &v[0].rotr(22));
+        let maj = maj(cs, &v[0], &v[1], &v[2]);
+
+        let T1 = [v[7].clone(), S1, ch, Word::constant(K[t]), w[t].clone()];
+        let e = add(cs, &[&T1[..], &[v[3].clone()]].concat())?;
+        let a = add(cs, &[&T1[..], &[S0, maj]].concat())?;
+
+        v.pop();
+        v.insert(0, a);
+        v[4] = e;
+    }
+
+    state
+        .iter()
+        .zip(v.iter())
+        .map(|(h, v)| add_bits(cs, &[h.clone(), v.clone()]))
+        .collect()
+}
+
+/// Constrains the SHA-256 digest of the message whose bits are
+/// `input_bits`, most significant bit of each byte first, which must
+/// already be constrained to bits.
+///
+/// Returns the 256 bits of the digest in the same order.  The length
+/// of the message is public, so its padding is added as constants.
+///
+/// The prover passes the `message` itself, and the verifier passes
+/// `None`.
+pub fn sha256_gadget<CS: ConstraintSystem>(
+    cs: &mut CS,
+    input_bits: &[Variable],
+    message: Option<&[u8]>,
+) -> Result<Vec<Variable>, R1CSError> {
+    assert_eq!(input_bits.len() % 8, 0);
+    if let Some(message) = message {
+        assert_eq!(input_bits.len(), 8 * message.len());
+    }
+
+    // Pad the message with a 1 bit, zeros, and the 64-bit length to a
+    // multiple of 512 bits.
+    let len = input_bits.len() / 8;
+    let mut padding = vec![0x80u8];
+    padding.resize((119 - len % 64) % 64 + 1, 0);
+    padding.extend_from_slice(&(input_bits.len() as u64).to_be_bytes());
+
+    let mut words = Vec::with_capacity((len + padding.len()) / 4);
+    for i in 0..(len + padding.len()) / 4 {
+        let bytes: Vec<Option<u8>> = (4 * i..4 * i + 4)
+            .map(|j| match j.checked_sub(len) {
+                Some(j) => Some(padding[j]),
+                None => message.map(|m| m[j]),
+            })
+            .collect();
+        let value = bytes
+            .iter()
+            .try_fold(0u32, |word, byte| byte.map(|b| (word << 8) | u32::from(b)));
+        if 4 * i + 4 <= len {
+            words.push(Word::from_be_bits(&input_bits[32 * i..32 * i + 32], value));
+        } else if 4 * i >= len {
+            words.push(Word::constant(value.unwrap()));
+        } else {
+            // A word mixing message bytes and padding.
+            let bits = (0..32)
+                .map(|k| match input_bits.get(32 * i + 31 - k) {
+                    Some(&bit) => bit.into(),
+                    None => {
+                        let byte = padding[4 * i + 3 - k / 8 - len];
+                        Scalar::from((byte >> (k % 8)) & 1).into()
+                    }
+                })
+                .collect();
+            words.push(Word { bits, value });
+        }
+    }
+
+    let mut state: Vec<Word> = H.iter().map(|&h| Word::constant(h)).collect();
+    let mut digest = Vec::new();
+    for block in words.chunks(16) {
+        let words = compress(cs, &state, block)?;
+        state = words
+            .iter()
+            .map(|(bits, value)| Word {
+                bits: bits.iter().map(|&b| b.into()).collect(),
+                value: *value,
+            })
+            .collect();
+        digest = words
+            .into_iter()
+            .flat_map(|(bits, _)| bits.into_iter().rev())
+            .collect();
+    }
+    Ok(digest)
+}
@@ -1,11 +1,16 @@
 //! Definition of linear combinations.
 
 use curve25519_dalek::scalar::Scalar;
+use std::collections::HashMap;
 use std::iter::FromIterator;
-use std::ops::{Add, Mul, Neg, Sub};
+use std::ops::{Add, Div, Index, IndexMut, Mul, Neg, Range, RangeFull, Sub};
+use std::slice;
 
 /// Represents a variable in a constraint system.
-#[derive(Copy, Clone, Debug)]
+///
+/// Variables are ordered by kind, in the order listed below, and then
+/// by their position in the corresponding witness vector.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Variable {
     /// Represents an external input specified by a commitment.
     Committed(usize),
@@ -19,6 +24,24 @@ pub enum Variable {
     One(),
 }
 
+impl Variable {
+    /// Returns the position of the variable in the witness vector for
+    /// its kind: the committed values, or the left inputs, right
+    /// inputs or outputs of the multipliers.  Variables are assigned
+    /// increasing positions as they are allocated.
+    ///
+    /// Returns `None` for [`Variable::One`], which has no witness.
+    pub fn witness_index(&self) -> Option<usize> {
+        match *self {
+            Variable::Committed(i)
+            | Variable::MultiplierLeft(i)
+            | Variable::MultiplierRight(i)
+            | Variable::MultiplierOutput(i) => Some(i),
+            Variable::One() => None,
+        }
+    }
+}
+
 impl From<Variable> for LinearCombination {
     fn from(v: Variable) -> LinearCombination {
         LinearCombination {
@@ -111,6 +134,220 @@ pub struct LinearCombination {
     pub(super) terms: Vec<(Variable, Scalar)>,
 }
 
+impl LinearCombination {
+    /// Returns the linear combination with each variable `v`
+    /// replaced by `f(v)`, keeping the coefficients unchanged.
+    ///
+    /// This can be used to move a linear combination between
+    /// constraint systems, e.g. when composing circuits built with
+    /// disjoint variable indices.
+    pub fn map_variables<F>(self, f: F) -> LinearCombination
+    where
+        F: Fn(Variable) -> Variable,
+    {
+        LinearCombination {
+            terms: self
+                .terms
+                .into_iter()
+                .map(|(var, coeff)| (f(var), coeff))
+                .collect(),
+        }
+    }
+
+    /// Returns the linear combination \\(\sum_i a_i \cdot b_i\\) of
+    /// variables \\(a_i\\) with coefficients \\(b_i\\).
+    ///
+    /// Panics if `a` and `b` have different lengths.
+    pub fn dot_product<A, B, S>(a: A, b: B) -> Self
+    where
+        A: IntoIterator<Item = Variable>,
+        B: IntoIterator<Item = S>,
+        S: Into<Scalar>,
+    {
+        let mut a = a.into_iter();
+        let mut b = b.into_iter();
+        let mut terms = Vec::with_capacity(a.size_hint().0);
+        loop {
+            match (a.next(), b.next()) {
+                (Some(var), Some(coeff)) => terms.push((var, coeff.into())),
+                (None, None) => break,
+                _ => panic!("LinearCombination::dot_product: lengths of vectors do not match"),
+            }
+        }
+        LinearCombination { terms }
+    }
+
+    /// Returns the linear combination of the `(variable, coefficient)`
+    /// pairs in `terms`, allocating space for all of them up front if
+    /// the iterator reports its length.
+    ///
+    /// For variables and coefficients held in separate slices, use
+    /// [`LinearCombination::dot_product_scalar`].
+    pub fn from_sparse<I, S>(terms: I) -> Self
+    where
+        I: IntoIterator<Item = (Variable, S)>,
+        S: Into<Scalar>,
+    {
+        let mut lc = LinearCombination::default();
+        lc.extend_weighted(terms);
+        lc
+    }
+
+    /// Appends the `(variable, coefficient)` pairs in `terms` to the
+    /// linear combination, reserving space for all of them up front
+    /// if the iterator reports its length.
+    pub fn extend_weighted<I, S>(&mut self, terms: I)
+    where
+        I: IntoIterator<Item = (Variable, S)>,
+        S: Into<Scalar>,
+    {
+        self.terms
+            .extend(terms.into_iter().map(|(var, coeff)| (var, coeff.into())));
+    }
+
+    /// Returns the terms of the linear combination as a slice of
+    /// `(Variable, Scalar)` pairs.
+    pub fn as_slice(&self) -> &[(Variable, Scalar)] {
+        &self.terms
+    }
+
+    /// Returns the terms of the linear combination as a mutable slice
+    /// of `(Variable, Scalar)` pairs.
+    pub fn as_mut_slice(&mut self) -> &mut [(Variable, Scalar)] {
+        &mut self.terms
+    }
+
+    /// Consumes the linear combination and returns its terms as a
+    /// vector of `(Variable, Scalar)` pairs, without copying them.
+    pub fn into_terms(self) -> Vec<(Variable, Scalar)> {
+        self.terms
+    }
+
+    /// Returns an iterator over the `(Variable, Scalar)` terms of the
+    /// linear combination.
+    ///
+    /// The iterator is double-ended and knows its exact length.
+    pub fn iter(&self) -> slice::Iter<'_, (Variable, Scalar)> {
+        self.terms.iter()
+    }
+
+    /// Returns an iterator that allows modifying each term of the
+    /// linear combination.
+    ///
+    /// The iterator is double-ended and knows its exact length.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, (Variable, Scalar)> {
+        self.terms.iter_mut()
+    }
+
+    /// Returns the number of terms with a non-zero coefficient, as an
+    /// approximation of the number of scalar multiplications needed
+    /// to evaluate the linear combination during verification.
+    ///
+    /// Terms are not combined, so a variable that occurs in several
+    /// terms is counted once for each of them.
+    pub fn cost(&self) -> usize {
+        self.terms
+            .iter()
+            .filter(|(_, coeff)| coeff != &Scalar::zero())
+            .count()
+    }
+
+    /// Returns the number of terms in which each variable occurs.
+    ///
+    /// Terms with a zero coefficient are counted too, so the counts
+    /// add up to the number of terms; a count above one marks a
+    /// variable whose terms [`LinearCombination::simplify`] would merge.
+    pub fn terms_count_by_variable(&self) -> HashMap<Variable, usize> {
+        let mut counts = HashMap::new();
+        for (var, _) in self.terms.iter() {
+            *counts.entry(*var).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Evaluates the linear combination with the variables assigned
+    /// the given `values`, where [`Variable::One`] is always assigned
+    /// one.
+    ///
+    /// Returns `None` if any other variable is missing from `values`.
+    pub fn evaluate_at(&self, values: &HashMap<Variable, Scalar>) -> Option<Scalar> {
+        self.terms
+            .iter()
+            .map(|(var, coeff)| match var {
+                Variable::One() => Some(*coeff),
+                var => values.get(var).map(|value| coeff * value),
+            })
+            .sum()
+    }
+
+    /// Removes and returns the term at position `idx`, shifting the
+    /// following terms to the left.
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn remove_term(&mut self, idx: usize) -> (Variable, Scalar) {
+        self.terms.remove(idx)
+    }
+
+    /// Removes and returns the term at position `idx`, replacing it
+    /// with the last term.  This does not preserve the order of the
+    /// terms, but is \\(O(1)\\).
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn swap_remove_term(&mut self, idx: usize) -> (Variable, Scalar) {
+        self.terms.swap_remove(idx)
+    }
+
+    /// Returns the linear combination of `vars` with public
+    /// coefficients `scalars`, see [`LinearCombination::dot_product`].
+    ///
+    /// Panics if `vars` and `scalars` have different lengths.
+    pub fn dot_product_scalar(vars: &[Variable], scalars: &[Scalar]) -> Self {
+        LinearCombination::dot_product(vars.iter().cloned(), scalars.iter().cloned())
+    }
+
+    /// Returns the canonical form of the linear combination, with the
+    /// terms sorted by variable, the terms of each variable merged
+    /// into one, and the terms with a zero coefficient removed.
+    ///
+    /// Variables are sorted by kind and then by
+    /// [`Variable::witness_index`], so two linear combinations are
+    /// equal as expressions if and only if their canonical forms
+    /// have the same terms.
+    pub fn simplify(&self) -> LinearCombination {
+        let mut terms = self.terms.clone();
+        terms.sort_by_key(|(var, _)| *var);
+
+        let mut merged: Vec<(Variable, Scalar)> = Vec::with_capacity(terms.len());
+        for (var, coeff) in terms {
+            match merged.last_mut() {
+                Some((last, sum)) if *last == var => *sum += coeff,
+                _ => merged.push((var, coeff)),
+            }
+        }
+        merged.retain(|(_, coeff)| coeff != &Scalar::zero());
+
+        LinearCombination { terms: merged }
+    }
+}
+
+/// Returns whether `a` and `b` are equal as expressions, that is,
+/// whether they evaluate to the same value for every assignment of
+/// their variables, by comparing their canonical forms
+/// (see [`LinearCombination::simplify`]).
+///
+/// This is also the meaning of `==` on linear combinations.
+pub fn lc_eq_simplified(a: &LinearCombination, b: &LinearCombination) -> bool {
+    a.simplify().terms == b.simplify().terms
+}
+
+impl PartialEq for LinearCombination {
+    fn eq(&self, other: &Self) -> bool {
+        lc_eq_simplified(self, other)
+    }
+}
+
+impl Eq for LinearCombination {}
+
 impl Default for LinearCombination {
     fn default() -> Self {
         LinearCombination { terms: Vec::new() }
@@ -139,6 +376,127 @@ impl<'a> FromIterator<&'a (Variable, Scalar)> for LinearCombination {
     }
 }
 
+// Access to the terms of linear combinations
+
+impl Index<usize> for LinearCombination {
+    type Output = (Variable, Scalar);
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.terms[index]
+    }
+}
+
+impl IndexMut<usize> for LinearCombination {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.terms[index]
+    }
+}
+
+impl Index<Range<usize>> for LinearCombination {
+    type Output = [(Variable, Scalar)];
+
+    fn index(&self, range: Range<usize>) -> &Self::Output {
+        &self.terms[range]
+    }
+}
+
+impl Index<RangeFull> for LinearCombination {
+    type Output = [(Variable, Scalar)];
+
+    fn index(&self, _: RangeFull) -> &Self::Output {
+        &self.terms[..]
+    }
+}
+
+// Building linear combinations with integer coefficients
+
+/// Converts a signed integer into a scalar, negating when needed.
+fn scalar_from_i64(x: i64) -> Scalar {
+    // i64::MIN wraps to itself, whose cast is 2^63 as wanted.
+    let abs = Scalar::from(x.wrapping_abs() as u64);
+    if x < 0 {
+        -abs
+    } else {
+        abs
+    }
+}
+
+/// A builder for [`LinearCombination`]s with small integer coefficients.
+///
+/// This is usually more readable than spelling out each coefficient
+/// as a `Scalar`:
+/// ```ignore
+/// let lc = LCBuilder::new()
+///     .add_var(v1)
+///     .add_weighted(v2, 3)
+///     .add_weighted(v3, -7)
+///     .build();
+/// ```
+/// The [`lc!`] macro offers a shorter syntax for the same thing.
+#[derive(Clone, Debug, Default)]
+pub struct LCBuilder {
+    terms: Vec<(Variable, Scalar)>,
+}
+
+impl LCBuilder {
+    /// Creates a builder for an empty linear combination.
+    pub fn new() -> Self {
+        LCBuilder { terms: Vec::new() }
+    }
+
+    /// Adds the term `var` with coefficient 1.
+    pub fn add_var(mut self, var: Variable) -> Self {
+        self.terms.push((var, Scalar::one()));
+        self
+    }
+
+    /// Adds the term `coeff * var`.
+    pub fn add_weighted(mut self, var: Variable, coeff: i64) -> Self {
+        self.terms.push((var, scalar_from_i64(coeff)));
+        self
+    }
+
+    /// Adds the constant term `c`.
+    pub fn add_constant(mut self, c: i64) -> Self {
+        self.terms.push((Variable::One(), scalar_from_i64(c)));
+        self
+    }
+
+    /// Returns the linear combination of the terms added so far.
+    pub fn build(self) -> LinearCombination {
+        LinearCombination { terms: self.terms }
+    }
+}
+
+/// Builds a [`LinearCombination`](::r1cs::LinearCombination) from
+/// a comma-separated list of terms with integer coefficients.
+///
+/// Each term is either a variable (`v`), a weighted variable
+/// (`3 * v`, `-7 * v`), or an integer constant (`5`, `+5`, `-5`):
+/// ```ignore
+/// let lc = lc![v1, 2 * v2, -3 * v3, +5];
+/// ```
+/// is the same as `v1 + 2*v2 - 3*v3 + 5`.
+#[macro_export]
+macro_rules! lc {
+    (@terms $b:expr ; $(,)*) => { $b };
+    (@terms $b:expr ; $c:literal * $v:expr , $($rest:tt)*) => {
+        lc!(@terms $b.add_weighted($v, $c) ; $($rest)*)
+    };
+    (@terms $b:expr ; + $c:literal , $($rest:tt)*) => {
+        lc!(@terms $b.add_constant($c) ; $($rest)*)
+    };
+    (@terms $b:expr ; $c:literal , $($rest:tt)*) => {
+        lc!(@terms $b.add_constant($c) ; $($rest)*)
+    };
+    (@terms $b:expr ; $v:expr , $($rest:tt)*) => {
+        lc!(@terms $b.add_var($v) ; $($rest)*)
+    };
+    ($($terms:tt)*) => {
+        lc!(@terms $crate::r1cs::LCBuilder::new() ; $($terms)* ,).build()
+    };
+}
+
 // Arithmetic on linear combinations
 
 impl<L: Into<LinearCombination>> Add<L> for LinearCombination {
@@ -195,3 +553,348 @@ impl<S: Into<Scalar>> Mul<S> for LinearCombination {
         self
     }
 }
+
+/// Divides the linear combination by a constant, returning `None`
+/// if the constant is zero.
+impl<S: Into<Scalar>> Div<S> for LinearCombination {
+    type Output = Option<Self>;
+
+    fn div(self, other: S) -> Self::Output {
+        let other = other.into();
+        if other == Scalar::zero() {
+            None
+        } else {
+            Some(self * other.invert())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_terms() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierLeft(1);
+        let mut lc = a * Scalar::from(2u64) + b * Scalar::from(3u64) + Scalar::from(5u64);
+
+        assert_eq!(lc[0], (a, Scalar::from(2u64)));
+        assert_eq!(lc[1], (b, Scalar::from(3u64)));
+        assert_eq!(lc[2], (Variable::One(), Scalar::from(5u64)));
+        assert_eq!(&lc[1..3], &lc.terms[1..3]);
+        assert_eq!(lc[..].len(), 3);
+
+        // Merge the second term into the first one
+        lc[0].1 += Scalar::from(3u64);
+        lc[1].1 = Scalar::zero();
+        assert_eq!(lc[0], (a, Scalar::from(5u64)));
+        assert_eq!(lc[1], (b, Scalar::zero()));
+    }
+
+    #[test]
+    fn slice_access() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierLeft(0);
+        let mut lc = a * Scalar::from(2u64) + b * Scalar::from(3u64) + Scalar::from(5u64);
+
+        // An evaluator working only on the slice of terms.
+        let eval = |terms: &[(Variable, Scalar)]| -> Scalar {
+            terms
+                .iter()
+                .map(|(var, coeff)| {
+                    coeff
+                        * match var {
+                            Variable::Committed(_) => Scalar::one(),
+                            Variable::MultiplierLeft(_) => Scalar::from(10u64),
+                            _ => Scalar::one(),
+                        }
+                })
+                .sum()
+        };
+        assert_eq!(lc.as_slice(), &lc.terms[..]);
+        assert_eq!(eval(lc.as_slice()), Scalar::from(37u64));
+
+        lc.as_mut_slice()[1].1 = Scalar::from(4u64);
+        assert_eq!(lc.terms[1], (b, Scalar::from(4u64)));
+        assert_eq!(eval(lc.as_slice()), Scalar::from(47u64));
+    }
+
+    #[test]
+    fn into_terms() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierOutput(1);
+        let lc = a - b + Scalar::from(4u64);
+
+        assert_eq!(
+            lc.into_terms(),
+            vec![
+                (a, Scalar::one()),
+                (b, -Scalar::one()),
+                (Variable::One(), Scalar::from(4u64))
+            ]
+        );
+    }
+
+    #[test]
+    fn simplify() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierLeft(0);
+        let c = Variable::MultiplierOutput(2);
+        let lc = c * Scalar::from(2u64) + Scalar::from(3u64) + b - a + c + a - b * 2u64;
+
+        assert_eq!(
+            lc.simplify().into_terms(),
+            vec![
+                (b, -Scalar::one()),
+                (c, Scalar::from(3u64)),
+                (Variable::One(), Scalar::from(3u64)),
+            ]
+        );
+        assert_eq!((a - a).simplify().into_terms(), vec![]);
+    }
+
+    #[test]
+    fn eq_simplified() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierRight(1);
+
+        let lc1 = a * 2u64 + b - Scalar::one();
+        let lc2 = b - Scalar::from(2u64) + a + a + b * 0u64 + Scalar::one();
+        assert!(lc_eq_simplified(&lc1, &lc2));
+        assert_eq!(lc1, lc2);
+        assert_eq!(a - a, LinearCombination::default());
+
+        // Variables of different kinds with the same index differ.
+        let lc3 = Variable::MultiplierLeft(0) * 2u64 + b - Scalar::one();
+        assert!(!lc_eq_simplified(&lc1, &lc3));
+        assert_ne!(lc1, lc3);
+        assert_ne!(lc1, lc1.clone() + Scalar::one());
+    }
+
+    #[test]
+    fn iterate_terms() {
+        let vars: Vec<_> = (0..5).map(Variable::Committed).collect();
+        let mut lc = LinearCombination::dot_product(vars.clone(), vec![1u64, 2, 3, 4, 5]);
+
+        assert_eq!(lc.iter().size_hint(), (5, Some(5)));
+        assert_eq!(lc.iter().len(), 5);
+        assert_eq!(lc.iter_mut().size_hint(), (5, Some(5)));
+        assert_eq!(
+            LinearCombination::default().iter().size_hint(),
+            (0, Some(0))
+        );
+
+        let reversed: Vec<_> = lc.iter().rev().map(|(var, _)| *var).collect();
+        assert_eq!(reversed, vars.iter().rev().cloned().collect::<Vec<_>>());
+        assert_eq!(lc.iter().next_back(), Some(&(vars[4], Scalar::from(5u64))));
+
+        for (_, coeff) in lc.iter_mut().rev().take(2) {
+            *coeff = Scalar::zero();
+        }
+        assert_eq!(lc[2].1, Scalar::from(3u64));
+        assert_eq!(lc[3].1, Scalar::zero());
+        assert_eq!(lc[4].1, Scalar::zero());
+    }
+
+    #[test]
+    fn div_by_constant() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierLeft(0);
+        let lc = a * Scalar::from(2u64) - b + Scalar::from(5u64);
+
+        let scaled = lc.clone() * Scalar::from(3u64);
+        assert_eq!(
+            (scaled.clone() / Scalar::from(3u64)).unwrap().terms,
+            lc.terms
+        );
+        assert_eq!((scaled / 3u64).unwrap().terms, lc.terms);
+
+        let halved = (lc.clone() / 2u64).unwrap();
+        assert_eq!(halved[0], (a, Scalar::one()));
+        assert_eq!((halved * 2u64).terms, lc.terms);
+
+        assert!((lc.clone() / Scalar::zero()).is_none());
+        assert!((lc / 0u64).is_none());
+    }
+
+    #[test]
+    fn cost_counts_nonzero_terms() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierLeft(0);
+
+        assert_eq!(LinearCombination::default().cost(), 0);
+        assert_eq!((a + b * Scalar::from(2u64) + Scalar::one()).cost(), 3);
+        assert_eq!((a + b * Scalar::zero()).cost(), 1);
+        assert_eq!((a + a).cost(), 2);
+    }
+
+    #[test]
+    fn count_terms_by_variable() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierLeft(0);
+        let lc = a + b * Scalar::from(2u64) + a - a + Scalar::one();
+
+        let counts = lc.terms_count_by_variable();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&a], 3);
+        assert_eq!(counts[&b], 1);
+        assert_eq!(counts[&Variable::One()], 1);
+        assert_eq!(counts.get(&Variable::MultiplierRight(0)), None);
+    }
+
+    #[test]
+    fn evaluate_at() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierLeft(0);
+        let lc = a * Scalar::from(3u64) - b + Scalar::from(5u64);
+
+        let mut values = HashMap::new();
+        values.insert(a, Scalar::from(2u64));
+        assert_eq!(lc.evaluate_at(&values), None);
+
+        values.insert(b, Scalar::from(4u64));
+        assert_eq!(lc.evaluate_at(&values), Some(Scalar::from(7u64)));
+        assert_eq!(
+            LinearCombination::default().evaluate_at(&values),
+            Some(Scalar::zero())
+        );
+    }
+
+    #[test]
+    fn dot_product() {
+        let vars = [
+            Variable::Committed(0),
+            Variable::MultiplierLeft(1),
+            Variable::MultiplierOutput(2),
+        ];
+        let scalars = [Scalar::from(2u64), -Scalar::one(), Scalar::from(9u64)];
+
+        let expected = vars[0] * scalars[0] + vars[1] * scalars[1] + vars[2] * scalars[2];
+        assert_eq!(
+            LinearCombination::dot_product(vars.iter().cloned(), scalars.iter().cloned()).terms,
+            expected.terms
+        );
+        assert_eq!(
+            LinearCombination::dot_product_scalar(&vars, &scalars).terms,
+            expected.terms
+        );
+
+        // Coefficients can be anything convertible into a scalar.
+        let powers = LinearCombination::dot_product(vars.iter().cloned(), vec![1u64, 2, 4]);
+        assert_eq!(powers[2], (vars[2], Scalar::from(4u64)));
+    }
+
+    #[test]
+    fn from_sparse() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierRight(3);
+        let terms = vec![(a, 2u64), (b, 5u64), (Variable::One(), 1u64)];
+
+        let lc = LinearCombination::from_sparse(terms);
+        assert_eq!(
+            lc.terms,
+            (a * Scalar::from(2u64) + b * Scalar::from(5u64) + Scalar::one()).terms
+        );
+
+        assert!(
+            LinearCombination::from_sparse(Vec::<(Variable, Scalar)>::new())
+                .terms
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn extend_weighted() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierLeft(1);
+        let mut lc = LinearCombination::from(a);
+
+        lc.extend_weighted(vec![(b, -Scalar::one()), (a, Scalar::from(3u64))]);
+        lc.extend_weighted(Some((Variable::One(), 9u64)));
+        assert_eq!(lc.terms, (a - b + a * Scalar::from(3u64) + 9u64).terms);
+    }
+
+    #[test]
+    #[should_panic(expected = "lengths of vectors do not match")]
+    fn dot_product_length_mismatch() {
+        LinearCombination::dot_product_scalar(&[Variable::One()], &[]);
+    }
+
+    #[test]
+    fn remove_terms() {
+        let a = Variable::Committed(0);
+        let b = Variable::Committed(1);
+        let c = Variable::Committed(2);
+        let d = Variable::Committed(3);
+        let mut lc = LinearCombination::dot_product(vec![a, b, c, d], vec![1u64, 2, 3, 4]);
+
+        assert_eq!(lc.remove_term(1), (b, Scalar::from(2u64)));
+        assert_eq!(
+            lc.terms,
+            vec![
+                (a, Scalar::from(1u64)),
+                (c, Scalar::from(3u64)),
+                (d, Scalar::from(4u64))
+            ]
+        );
+
+        assert_eq!(lc.swap_remove_term(0), (a, Scalar::from(1u64)));
+        assert_eq!(
+            lc.terms,
+            vec![(d, Scalar::from(4u64)), (c, Scalar::from(3u64))]
+        );
+    }
+
+    #[test]
+    fn builder_matches_operators() {
+        let v1 = Variable::Committed(0);
+        let v2 = Variable::MultiplierLeft(1);
+        let v3 = Variable::MultiplierOutput(2);
+
+        let expected = v1 + (v2 * Scalar::from(3u64)) - (v3 * Scalar::from(7u64));
+        let built = LCBuilder::new()
+            .add_var(v1)
+            .add_weighted(v2, 3)
+            .add_weighted(v3, -7)
+            .build();
+        assert_eq!(built.terms, expected.terms);
+
+        let expected = expected + Scalar::from(5u64) - Scalar::from(2u64);
+        let built = lc![v1, 3 * v2, -7 * v3, +5, -2];
+        assert_eq!(built.terms, expected.terms);
+
+        assert!(lc![].terms.is_empty());
+        assert_eq!(
+            LCBuilder::new()
+                .add_constant(i64::min_value())
+                .build()
+                .terms,
+            vec![(Variable::One(), -Scalar::from(1u64 << 63))]
+        );
+    }
+
+    #[test]
+    fn map_variables() {
+        let a = Variable::Committed(0);
+        let b = Variable::MultiplierOutput(1);
+        let lc = a * Scalar::from(2u64) - b + Scalar::from(3u64);
+
+        // Shift the multiplier indices, as when appending a circuit
+        // after another one with 4 multipliers.
+        let shifted = lc.map_variables(|var| match var {
+            Variable::MultiplierLeft(i) => Variable::MultiplierLeft(i + 4),
+            Variable::MultiplierRight(i) => Variable::MultiplierRight(i + 4),
+            Variable::MultiplierOutput(i) => Variable::MultiplierOutput(i + 4),
+            var => var,
+        });
+        assert_eq!(
+            shifted.terms,
+            vec![
+                (a, Scalar::from(2u64)),
+                (Variable::MultiplierOutput(5), -Scalar::one()),
+                (Variable::One(), Scalar::from(3u64)),
+            ]
+        );
+    }
+}
@@ -3,16 +3,20 @@
 #[doc(include = "../docs/cs-proof.md")]
 mod notes {}
 
+mod budget;
 mod constraint_system;
 mod linear_combination;
 mod proof;
 mod prover;
 mod verifier;
 
-pub use self::constraint_system::ConstraintSystem;
-pub use self::linear_combination::{LinearCombination, Variable};
+pub mod gadgets;
+
+pub use self::budget::BudgetedCS;
+pub use self::constraint_system::{ConstraintSystem, ConstraintSystemStats};
+pub use self::linear_combination::{lc_eq_simplified, LCBuilder, LinearCombination, Variable};
 pub use self::proof::R1CSProof;
 pub use self::prover::Prover;
-pub use self::verifier::Verifier;
+pub use self::verifier::{batch_verify, Verifier};
 
 pub use errors::R1CSError;
@@ -1,11 +1,13 @@
 #![allow(non_snake_case)]
 
 use clear_on_drop::clear::Clear;
+
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::MultiscalarMul;
 use merlin::Transcript;
 
+use super::constraint_system::next_state_id;
 use super::{ConstraintSystem, LinearCombination, R1CSProof, Variable};
 
 use errors::R1CSError;
@@ -39,16 +41,8 @@ pub struct ProverCS<'a, 'b> {
     transcript: &'a mut Transcript,
     bp_gens: &'b BulletproofGens,
     pc_gens: &'b PedersenGens,
-    /// The constraints accumulated so far.
-    constraints: Vec<LinearCombination>,
-    /// Stores assignments to the "left" of multiplication gates
-    a_L: Vec<Scalar>,
-    /// Stores assignments to the "right" of multiplication gates
-    a_R: Vec<Scalar>,
-    /// Stores assignments to the "output" of multiplication gates
-    a_O: Vec<Scalar>,
-    /// High-level witness data (value openings to V commitments)
-    v: Vec<Scalar>,
+    /// The constraints and assignments accumulated so far.
+    state: ProverState,
     /// High-level witness data (blinding openings to V commitments)
     v_blinding: Vec<Scalar>,
 }
@@ -56,28 +50,86 @@ pub struct ProverCS<'a, 'b> {
 /// Overwrite secrets with null bytes when they go out of scope.
 impl<'a, 'b> Drop for ProverCS<'a, 'b> {
     fn drop(&mut self) {
-        self.v.clear();
-        self.v_blinding.clear();
-
         // Important: due to how ClearOnDrop auto-implements InitializableFromZeroed
         // for T: Default, calling .clear() on Vec compiles, but does not
         // clear the content. Instead, it only clears the Vec's header.
         // Clearing the underlying buffer item-by-item will do the job, but will
         // keep the header as-is, which is fine since the header does not contain secrets.
-        for e in self.a_L.iter_mut() {
+        for e in self.v_blinding.iter_mut() {
             e.clear();
         }
-        for e in self.a_R.iter_mut() {
-            e.clear();
-        }
-        for e in self.a_O.iter_mut() {
+        // The assignments are cleared when the state is dropped.
+        // XXX use ClearOnDrop instead of doing the above
+    }
+}
+
+/// The constraints and assignments of a prover's constraint system,
+/// shared by [`ProverCS`] and its forks.
+#[derive(Clone)]
+struct ProverState {
+    /// The constraints accumulated so far.
+    constraints: Vec<LinearCombination>,
+    /// Stores assignments to the "left" of multiplication gates
+    a_L: Vec<Scalar>,
+    /// Stores assignments to the "right" of multiplication gates
+    a_R: Vec<Scalar>,
+    /// Stores assignments to the "output" of multiplication gates
+    a_O: Vec<Scalar>,
+    /// High-level witness data (value openings to V commitments)
+    v: Vec<Scalar>,
+    /// Identifies the state, and changes whenever it is modified.
+    id: usize,
+}
+
+/// Overwrite secrets with null bytes when they go out of scope.
+impl Drop for ProverState {
+    fn drop(&mut self) {
+        for e in self
+            .v
+            .iter_mut()
+            .chain(self.a_L.iter_mut())
+            .chain(self.a_R.iter_mut())
+            .chain(self.a_O.iter_mut())
+        {
             e.clear();
         }
-        // XXX use ClearOnDrop instead of doing the above
     }
 }
 
-impl<'a, 'b> ConstraintSystem for ProverCS<'a, 'b> {
+impl ProverState {
+    fn new() -> Self {
+        ProverState {
+            constraints: Vec::new(),
+            a_L: Vec::new(),
+            a_R: Vec::new(),
+            a_O: Vec::new(),
+            v: Vec::new(),
+            id: next_state_id(),
+        }
+    }
+
+    /// Marks the state as modified.
+    fn touch(&mut self) {
+        self.id = next_state_id();
+    }
+
+    /// Evaluates `lc` with the current assignments.
+    fn eval(&self, lc: &LinearCombination) -> Scalar {
+        lc.terms
+            .iter()
+            .map(|(var, coeff)| {
+                coeff
+                    * match var {
+                        Variable::MultiplierLeft(i) => self.a_L[*i],
+                        Variable::MultiplierRight(i) => self.a_R[*i],
+                        Variable::MultiplierOutput(i) => self.a_O[*i],
+                        Variable::Committed(i) => self.v[*i],
+                        Variable::One() => Scalar::one(),
+                    }
+            })
+            .sum()
+    }
+
     fn multiply(
         &mut self,
         mut left: LinearCombination,
@@ -88,14 +140,7 @@ impl<'a, 'b> ConstraintSystem for ProverCS<'a, 'b> {
         let r = self.eval(&right);
         let o = l * r;
 
-        // Create variables for l,r,o ...
-        let l_var = Variable::MultiplierLeft(self.a_L.len());
-        let r_var = Variable::MultiplierRight(self.a_R.len());
-        let o_var = Variable::MultiplierOutput(self.a_O.len());
-        // ... and assign them
-        self.a_L.push(l);
-        self.a_R.push(r);
-        self.a_O.push(o);
+        let (l_var, r_var, o_var) = self.allocate((l, r, o));
 
         // Constrain l,r,o:
         left.terms.push((l_var, -Scalar::one()));
@@ -106,11 +151,8 @@ impl<'a, 'b> ConstraintSystem for ProverCS<'a, 'b> {
         (l_var, r_var, o_var)
     }
 
-    fn allocate<F>(&mut self, assign_fn: F) -> Result<(Variable, Variable, Variable), R1CSError>
-    where
-        F: FnOnce() -> Result<(Scalar, Scalar, Scalar), R1CSError>,
-    {
-        let (l, r, o) = assign_fn()?;
+    fn allocate(&mut self, (l, r, o): (Scalar, Scalar, Scalar)) -> (Variable, Variable, Variable) {
+        self.touch();
 
         // Create variables for l,r,o ...
         let l_var = Variable::MultiplierLeft(self.a_L.len());
@@ -121,18 +163,188 @@ impl<'a, 'b> ConstraintSystem for ProverCS<'a, 'b> {
         self.a_R.push(r);
         self.a_O.push(o);
 
-        Ok((l_var, r_var, o_var))
+        (l_var, r_var, o_var)
     }
 
     fn constrain(&mut self, lc: LinearCombination) {
         // TODO: check that the linear combinations are valid
         // (e.g. that variables are valid, that the linear combination evals to 0 for prover, etc).
+        self.touch();
         self.constraints.push(lc);
     }
 
+    fn assert_nonzero(&mut self, var: Variable) -> Result<(), R1CSError> {
+        let value = self.eval(&var.into());
+        if value == Scalar::zero() {
+            return Err(R1CSError::WitnessIsZero);
+        }
+
+        // Allocate var * inv = 1, and constrain the left input to var.
+        let (l_var, _, o_var) = self.allocate((value, value.invert(), Scalar::one()));
+        self.constrain(l_var - var);
+        self.constrain(o_var - Scalar::one());
+        Ok(())
+    }
+
+    fn total_verification_cost(&self) -> usize {
+        self.constraints.iter().map(|lc| lc.cost()).sum()
+    }
+
+    /// Creates a fork with the given transcript state.
+    fn fork(&self, transcript: Transcript, pc_gens: PedersenGens) -> ProverCSFork {
+        ProverCSFork {
+            transcript,
+            state: self.clone(),
+            pc_gens,
+            base: self.id,
+        }
+    }
+
+    /// Returns the state of `fork`, after checking that it was
+    /// created from this state.
+    fn merge_fork(&self, fork: ProverCSFork) -> (Transcript, ProverState) {
+        assert_eq!(
+            fork.base, self.id,
+            "fork was not created from the current state of the constraint system"
+        );
+        (fork.transcript, fork.state)
+    }
+}
+
+/// An independent copy of a [`ProverCS`], created by
+/// [`ConstraintSystem::fork`].
+#[derive(Clone)]
+pub struct ProverCSFork {
+    transcript: Transcript,
+    state: ProverState,
+    pc_gens: PedersenGens,
+    /// The identifier of the state the fork was created from.
+    base: usize,
+}
+
+impl ConstraintSystem for ProverCSFork {
+    type Fork = ProverCSFork;
+
+    fn multiply(
+        &mut self,
+        left: LinearCombination,
+        right: LinearCombination,
+    ) -> (Variable, Variable, Variable) {
+        self.state.multiply(left, right)
+    }
+
+    fn allocate<F>(&mut self, assign_fn: F) -> Result<(Variable, Variable, Variable), R1CSError>
+    where
+        F: FnOnce() -> Result<(Scalar, Scalar, Scalar), R1CSError>,
+    {
+        Ok(self.state.allocate(assign_fn()?))
+    }
+
+    fn constrain(&mut self, lc: LinearCombination) {
+        self.state.constrain(lc)
+    }
+
+    fn assert_nonzero(&mut self, var: Variable) -> Result<(), R1CSError> {
+        self.state.assert_nonzero(var)
+    }
+
+    fn committed_len(&self) -> usize {
+        self.state.v.len()
+    }
+
+    fn multipliers_len(&self) -> usize {
+        self.state.a_L.len()
+    }
+
+    fn constraints_len(&self) -> usize {
+        self.state.constraints.len()
+    }
+
+    fn total_verification_cost(&self) -> usize {
+        self.state.total_verification_cost()
+    }
+
+    fn fork(&self) -> ProverCSFork {
+        self.state.fork(self.transcript.clone(), self.pc_gens)
+    }
+
+    fn merge_fork(&mut self, fork: ProverCSFork) {
+        let (transcript, state) = self.state.merge_fork(fork);
+        self.transcript = transcript;
+        self.state = state;
+    }
+
     fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        self.state.touch();
         self.transcript.challenge_scalar(label)
     }
+
+    fn pedersen_gens(&self) -> &PedersenGens {
+        &self.pc_gens
+    }
+}
+
+impl<'a, 'b> ConstraintSystem for ProverCS<'a, 'b> {
+    type Fork = ProverCSFork;
+
+    fn multiply(
+        &mut self,
+        left: LinearCombination,
+        right: LinearCombination,
+    ) -> (Variable, Variable, Variable) {
+        self.state.multiply(left, right)
+    }
+
+    fn allocate<F>(&mut self, assign_fn: F) -> Result<(Variable, Variable, Variable), R1CSError>
+    where
+        F: FnOnce() -> Result<(Scalar, Scalar, Scalar), R1CSError>,
+    {
+        Ok(self.state.allocate(assign_fn()?))
+    }
+
+    fn constrain(&mut self, lc: LinearCombination) {
+        self.state.constrain(lc)
+    }
+
+    fn assert_nonzero(&mut self, var: Variable) -> Result<(), R1CSError> {
+        self.state.assert_nonzero(var)
+    }
+
+    fn committed_len(&self) -> usize {
+        self.state.v.len()
+    }
+
+    fn multipliers_len(&self) -> usize {
+        self.state.a_L.len()
+    }
+
+    fn constraints_len(&self) -> usize {
+        self.state.constraints.len()
+    }
+
+    fn total_verification_cost(&self) -> usize {
+        self.state.total_verification_cost()
+    }
+
+    fn fork(&self) -> ProverCSFork {
+        self.state.fork(self.transcript.clone(), *self.pc_gens)
+    }
+
+    fn merge_fork(&mut self, fork: ProverCSFork) {
+        let (transcript, state) = self.state.merge_fork(fork);
+        *self.transcript = transcript;
+        // The previous state clears its assignments when dropped.
+        self.state = state;
+    }
+
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        self.state.touch();
+        self.transcript.challenge_scalar(label)
+    }
+
+    fn pedersen_gens(&self) -> &PedersenGens {
+        self.pc_gens
+    }
 }
 
 impl<'a, 'b> Prover<'a, 'b> {
@@ -169,12 +381,8 @@ impl<'a, 'b> Prover<'a, 'b> {
                 pc_gens,
                 bp_gens,
                 transcript,
-                v: Vec::new(),
+                state: ProverState::new(),
                 v_blinding: Vec::new(),
-                constraints: Vec::new(),
-                a_L: Vec::new(),
-                a_R: Vec::new(),
-                a_O: Vec::new(),
             },
         }
     }
@@ -199,7 +407,7 @@ impl<'a, 'b> Prover<'a, 'b> {
     pub fn commit(&mut self, v: Scalar, v_blinding: Scalar) -> (CompressedRistretto, Variable) {
         let i = self.m as usize;
         self.m += 1;
-        self.cs.v.push(v);
+        self.cs.state.v.push(v);
         self.cs.v_blinding.push(v_blinding);
 
         // Add the commitment to the transcript.
@@ -209,6 +417,38 @@ impl<'a, 'b> Prover<'a, 'b> {
         (V, Variable::Committed(i))
     }
 
+    /// Creates commitments to a batch of high-level variables and
+    /// adds them to the transcript.
+    ///
+    /// This is equivalent to calling [`Prover::commit`] for each
+    /// pair of `values` and `blindings` in turn, so the resulting
+    /// proof can be verified either way.
+    ///
+    /// # Returns
+    ///
+    /// Returns a pair of the Pedersen commitments and the
+    /// [`Variable`]s corresponding to them, or
+    /// [`R1CSError::WrongNumBlindingFactors`] if `values` and
+    /// `blindings` have different lengths.
+    pub fn commit_batch(
+        &mut self,
+        values: &[Scalar],
+        blindings: &[Scalar],
+    ) -> Result<(Vec<CompressedRistretto>, Vec<Variable>), R1CSError> {
+        if values.len() != blindings.len() {
+            return Err(R1CSError::WrongNumBlindingFactors);
+        }
+
+        self.cs.state.v.reserve(values.len());
+        self.cs.v_blinding.reserve(values.len());
+
+        Ok(values
+            .iter()
+            .zip(blindings.iter())
+            .map(|(v, v_blinding)| self.commit(*v, *v_blinding))
+            .unzip())
+    }
+
     /// Consume the `Prover`, provide the `ConstraintSystem` implementation to the closure,
     /// and produce a proof.
     pub fn finalize_inputs(self) -> ProverCS<'a, 'b> {
@@ -237,8 +477,8 @@ impl<'a, 'b> ProverCS<'a, 'b> {
         &mut self,
         z: &Scalar,
     ) -> (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Vec<Scalar>) {
-        let n = self.a_L.len();
-        let m = self.v.len();
+        let n = self.state.a_L.len();
+        let m = self.state.v.len();
 
         let mut wL = vec![Scalar::zero(); n];
         let mut wR = vec![Scalar::zero(); n];
@@ -246,7 +486,7 @@ impl<'a, 'b> ProverCS<'a, 'b> {
         let mut wV = vec![Scalar::zero(); m];
 
         let mut exp_z = *z;
-        for lc in self.constraints.iter() {
+        for lc in self.state.constraints.iter() {
             for (var, coeff) in &lc.terms {
                 match var {
                     Variable::MultiplierLeft(i) => {
@@ -272,22 +512,6 @@ impl<'a, 'b> ProverCS<'a, 'b> {
         (wL, wR, wO, wV)
     }
 
-    fn eval(&self, lc: &LinearCombination) -> Scalar {
-        lc.terms
-            .iter()
-            .map(|(var, coeff)| {
-                coeff
-                    * match var {
-                        Variable::MultiplierLeft(i) => self.a_L[*i],
-                        Variable::MultiplierRight(i) => self.a_R[*i],
-                        Variable::MultiplierOutput(i) => self.a_O[*i],
-                        Variable::Committed(i) => self.v[*i],
-                        Variable::One() => Scalar::one(),
-                    }
-            })
-            .sum()
-    }
-
     /// Consume this `ConstraintSystem` to produce a proof.
     pub fn prove(mut self) -> Result<R1CSProof, R1CSError> {
         use std::iter;
@@ -296,8 +520,8 @@ impl<'a, 'b> ProverCS<'a, 'b> {
         // 0. Pad zeros to the next power of two (or do that implicitly when creating vectors)
 
         // If the number of multiplications is not 0 or a power of 2, then pad the circuit.
-        let n = self.a_L.len();
-        let padded_n = self.a_L.len().next_power_of_two();
+        let n = self.state.a_L.len();
+        let padded_n = self.state.a_L.len().next_power_of_two();
         let pad = padded_n - n;
 
         if self.bp_gens.gens_capacity < padded_n {
@@ -344,8 +568,8 @@ impl<'a, 'b> ProverCS<'a, 'b> {
         // A_I = <a_L, G> + <a_R, H> + i_blinding * B_blinding
         let A_I = RistrettoPoint::multiscalar_mul(
             iter::once(&i_blinding)
-                .chain(self.a_L.iter())
-                .chain(self.a_R.iter()),
+                .chain(self.state.a_L.iter())
+                .chain(self.state.a_R.iter()),
             iter::once(&self.pc_gens.B_blinding)
                 .chain(gens.G(n))
                 .chain(gens.H(n)),
@@ -354,7 +578,7 @@ impl<'a, 'b> ProverCS<'a, 'b> {
 
         // A_O = <a_O, G> + o_blinding * B_blinding
         let A_O = RistrettoPoint::multiscalar_mul(
-            iter::once(&o_blinding).chain(self.a_O.iter()),
+            iter::once(&o_blinding).chain(self.state.a_O.iter()),
             iter::once(&self.pc_gens.B_blinding).chain(gens.G(n)),
         )
         .compress();
@@ -389,15 +613,15 @@ impl<'a, 'b> ProverCS<'a, 'b> {
         for i in 0..n {
             // l_poly.0 = 0
             // l_poly.1 = a_L + y^-n * (z * z^Q * W_R)
-            l_poly.1[i] = self.a_L[i] + exp_y_inv[i] * wR[i];
+            l_poly.1[i] = self.state.a_L[i] + exp_y_inv[i] * wR[i];
             // l_poly.2 = a_O
-            l_poly.2[i] = self.a_O[i];
+            l_poly.2[i] = self.state.a_O[i];
             // l_poly.3 = s_L
             l_poly.3[i] = s_L[i];
             // r_poly.0 = (z * z^Q * W_O) - y^n
             r_poly.0[i] = wO[i] - exp_y;
             // r_poly.1 = y^n * a_R + (z * z^Q * W_L)
-            r_poly.1[i] = exp_y * self.a_R[i] + wL[i];
+            r_poly.1[i] = exp_y * self.state.a_R[i] + wL[i];
             // r_poly.2 = 0
             // r_poly.3 = y^n * s_R
             r_poly.3[i] = exp_y * s_R[i];
@@ -2,9 +2,12 @@
 
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::VartimeMultiscalarMul;
-use merlin::Transcript;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+use merlin::{Transcript, TranscriptRng};
+use std::iter;
+use std::ptr;
 
+use super::constraint_system::next_state_id;
 use super::{ConstraintSystem, LinearCombination, R1CSProof, Variable};
 
 use errors::R1CSError;
@@ -37,6 +40,15 @@ pub struct VerifierCS<'a, 'b> {
     bp_gens: &'b BulletproofGens,
     pc_gens: &'b PedersenGens,
     transcript: &'a mut Transcript,
+    /// The constraints and variables accumulated so far.
+    state: VerifierState,
+    V: Vec<CompressedRistretto>,
+}
+
+/// The constraints and variables of a verifier's constraint system,
+/// shared by [`VerifierCS`] and its forks.
+#[derive(Clone)]
+struct VerifierState {
     constraints: Vec<LinearCombination>,
     /// Records the number of low-level variables allocated in the
     /// constraint system.
@@ -46,22 +58,30 @@ pub struct VerifierCS<'a, 'b> {
     /// `Missing`), so the `num_vars` isn't kept implicitly in the
     /// variable assignments.
     num_vars: usize,
-    V: Vec<CompressedRistretto>,
+    /// Identifies the state, and changes whenever it is modified.
+    id: usize,
 }
 
-impl<'a, 'b> ConstraintSystem for VerifierCS<'a, 'b> {
+impl VerifierState {
+    fn new() -> Self {
+        VerifierState {
+            constraints: Vec::new(),
+            num_vars: 0,
+            id: next_state_id(),
+        }
+    }
+
+    /// Marks the state as modified.
+    fn touch(&mut self) {
+        self.id = next_state_id();
+    }
+
     fn multiply(
         &mut self,
         mut left: LinearCombination,
         mut right: LinearCombination,
     ) -> (Variable, Variable, Variable) {
-        let var = self.num_vars;
-        self.num_vars += 1;
-
-        // Create variables for l,r,o
-        let l_var = Variable::MultiplierLeft(var);
-        let r_var = Variable::MultiplierRight(var);
-        let o_var = Variable::MultiplierOutput(var);
+        let (l_var, r_var, o_var) = self.allocate();
 
         // Constrain l,r,o:
         left.terms.push((l_var, -Scalar::one()));
@@ -72,10 +92,9 @@ impl<'a, 'b> ConstraintSystem for VerifierCS<'a, 'b> {
         (l_var, r_var, o_var)
     }
 
-    fn allocate<F>(&mut self, _: F) -> Result<(Variable, Variable, Variable), R1CSError>
-    where
-        F: FnOnce() -> Result<(Scalar, Scalar, Scalar), R1CSError>,
-    {
+    fn allocate(&mut self) -> (Variable, Variable, Variable) {
+        self.touch();
+
         let var = self.num_vars;
         self.num_vars += 1;
 
@@ -84,19 +103,195 @@ impl<'a, 'b> ConstraintSystem for VerifierCS<'a, 'b> {
         let r_var = Variable::MultiplierRight(var);
         let o_var = Variable::MultiplierOutput(var);
 
-        Ok((l_var, r_var, o_var))
+        (l_var, r_var, o_var)
     }
 
     fn constrain(&mut self, lc: LinearCombination) {
         // TODO: check that the linear combinations are valid
         // (e.g. that variables are valid, that the linear combination
         // evals to 0 for prover, etc).
+        self.touch();
         self.constraints.push(lc);
     }
 
+    fn assert_nonzero(&mut self, var: Variable) {
+        // Allocate var * inv = 1, and constrain the left input to var.
+        let (l_var, _, o_var) = self.allocate();
+        self.constrain(l_var - var);
+        self.constrain(o_var - Scalar::one());
+    }
+
+    fn total_verification_cost(&self) -> usize {
+        self.constraints.iter().map(|lc| lc.cost()).sum()
+    }
+
+    /// Creates a fork with the given transcript state.
+    fn fork(
+        &self,
+        transcript: Transcript,
+        num_committed: usize,
+        pc_gens: PedersenGens,
+    ) -> VerifierCSFork {
+        VerifierCSFork {
+            transcript,
+            state: self.clone(),
+            num_committed,
+            pc_gens,
+            base: self.id,
+        }
+    }
+
+    /// Returns the state of `fork`, after checking that it was
+    /// created from this state.
+    fn merge_fork(&self, fork: VerifierCSFork) -> (Transcript, VerifierState) {
+        assert_eq!(
+            fork.base, self.id,
+            "fork was not created from the current state of the constraint system"
+        );
+        (fork.transcript, fork.state)
+    }
+}
+
+/// An independent copy of a [`VerifierCS`], created by
+/// [`ConstraintSystem::fork`].
+#[derive(Clone)]
+pub struct VerifierCSFork {
+    transcript: Transcript,
+    state: VerifierState,
+    /// The number of committed variables, which cannot change once
+    /// the inputs are finalized.
+    num_committed: usize,
+    pc_gens: PedersenGens,
+    /// The identifier of the state the fork was created from.
+    base: usize,
+}
+
+impl ConstraintSystem for VerifierCSFork {
+    type Fork = VerifierCSFork;
+
+    fn multiply(
+        &mut self,
+        left: LinearCombination,
+        right: LinearCombination,
+    ) -> (Variable, Variable, Variable) {
+        self.state.multiply(left, right)
+    }
+
+    fn allocate<F>(&mut self, _: F) -> Result<(Variable, Variable, Variable), R1CSError>
+    where
+        F: FnOnce() -> Result<(Scalar, Scalar, Scalar), R1CSError>,
+    {
+        Ok(self.state.allocate())
+    }
+
+    fn constrain(&mut self, lc: LinearCombination) {
+        self.state.constrain(lc)
+    }
+
+    fn assert_nonzero(&mut self, var: Variable) -> Result<(), R1CSError> {
+        self.state.assert_nonzero(var);
+        Ok(())
+    }
+
+    fn committed_len(&self) -> usize {
+        self.num_committed
+    }
+
+    fn multipliers_len(&self) -> usize {
+        self.state.num_vars
+    }
+
+    fn constraints_len(&self) -> usize {
+        self.state.constraints.len()
+    }
+
+    fn total_verification_cost(&self) -> usize {
+        self.state.total_verification_cost()
+    }
+
+    fn fork(&self) -> VerifierCSFork {
+        self.state
+            .fork(self.transcript.clone(), self.num_committed, self.pc_gens)
+    }
+
+    fn merge_fork(&mut self, fork: VerifierCSFork) {
+        let (transcript, state) = self.state.merge_fork(fork);
+        self.transcript = transcript;
+        self.state = state;
+    }
+
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        self.state.touch();
+        self.transcript.challenge_scalar(label)
+    }
+
+    fn pedersen_gens(&self) -> &PedersenGens {
+        &self.pc_gens
+    }
+}
+
+impl<'a, 'b> ConstraintSystem for VerifierCS<'a, 'b> {
+    type Fork = VerifierCSFork;
+
+    fn multiply(
+        &mut self,
+        left: LinearCombination,
+        right: LinearCombination,
+    ) -> (Variable, Variable, Variable) {
+        self.state.multiply(left, right)
+    }
+
+    fn allocate<F>(&mut self, _: F) -> Result<(Variable, Variable, Variable), R1CSError>
+    where
+        F: FnOnce() -> Result<(Scalar, Scalar, Scalar), R1CSError>,
+    {
+        Ok(self.state.allocate())
+    }
+
+    fn constrain(&mut self, lc: LinearCombination) {
+        self.state.constrain(lc)
+    }
+
+    fn assert_nonzero(&mut self, var: Variable) -> Result<(), R1CSError> {
+        self.state.assert_nonzero(var);
+        Ok(())
+    }
+
+    fn committed_len(&self) -> usize {
+        self.V.len()
+    }
+
+    fn multipliers_len(&self) -> usize {
+        self.state.num_vars
+    }
+
+    fn constraints_len(&self) -> usize {
+        self.state.constraints.len()
+    }
+
+    fn total_verification_cost(&self) -> usize {
+        self.state.total_verification_cost()
+    }
+
+    fn fork(&self) -> VerifierCSFork {
+        self.state
+            .fork(self.transcript.clone(), self.V.len(), *self.pc_gens)
+    }
+
+    fn merge_fork(&mut self, fork: VerifierCSFork) {
+        let (transcript, state) = self.state.merge_fork(fork);
+        *self.transcript = transcript;
+        self.state = state;
+    }
+
     fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        self.state.touch();
         self.transcript.challenge_scalar(label)
     }
+
+    fn pedersen_gens(&self) -> &PedersenGens {
+        self.pc_gens
+    }
 }
 
 impl<'a, 'b> Verifier<'a, 'b> {
@@ -144,9 +339,8 @@ impl<'a, 'b> Verifier<'a, 'b> {
                 bp_gens,
                 pc_gens,
                 transcript,
-                num_vars: 0,
+                state: VerifierState::new(),
                 V: Vec::new(),
-                constraints: Vec::new(),
             },
         }
     }
@@ -176,6 +370,20 @@ impl<'a, 'b> Verifier<'a, 'b> {
         Variable::Committed(i)
     }
 
+    /// Adds a batch of commitments to high-level variables to the
+    /// transcript.
+    ///
+    /// This is equivalent to calling [`Verifier::commit`] for each
+    /// of the `commitments` in turn.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`Variable`]s corresponding to the `commitments`.
+    pub fn commit_batch(&mut self, commitments: &[CompressedRistretto]) -> Vec<Variable> {
+        self.cs.V.reserve(commitments.len());
+        commitments.iter().map(|V| self.commit(*V)).collect()
+    }
+
     /// Consume the `Verifier`, provide the `ConstraintSystem` implementation to the closure,
     /// and verify the proof against the resulting constraint system.
     pub fn finalize_inputs(self) -> VerifierCS<'a, 'b> {
@@ -208,7 +416,7 @@ impl<'a, 'b> VerifierCS<'a, 'b> {
         &mut self,
         z: &Scalar,
     ) -> (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Scalar) {
-        let n = self.num_vars;
+        let n = self.state.num_vars;
         let m = self.V.len();
 
         let mut wL = vec![Scalar::zero(); n];
@@ -218,7 +426,7 @@ impl<'a, 'b> VerifierCS<'a, 'b> {
         let mut wc = Scalar::zero();
 
         let mut exp_z = *z;
-        for lc in self.constraints.iter() {
+        for lc in self.state.constraints.iter() {
             for (var, coeff) in &lc.terms {
                 match var {
                     Variable::MultiplierLeft(i) => {
@@ -246,20 +454,51 @@ impl<'a, 'b> VerifierCS<'a, 'b> {
 
     /// Consume this `VerifierCS` and attempt to verify the supplied `proof`.
     pub fn verify(mut self, proof: &R1CSProof) -> Result<(), R1CSError> {
+        let scalars = self.verification_scalars(proof)?;
+
+        // We are performing a single-party circuit proof, so party index is 0.
+        let gens = self.bp_gens.share(0);
+        let padded_n = scalars.padded_n;
+
+        let mega_check = RistrettoPoint::optional_multiscalar_mul(
+            scalars
+                .dynamic_scalars()
+                .chain(iter::once(scalars.B_scalar())) // B
+                .chain(iter::once(scalars.B_blinding_scalar())) // B_blinding
+                .chain(scalars.g_scalars()) // G
+                .chain(scalars.h_scalars()), // H
+            dynamic_points(proof, &self.V)
+                .chain(iter::once(Some(self.pc_gens.B)))
+                .chain(iter::once(Some(self.pc_gens.B_blinding)))
+                .chain(gens.G(padded_n).map(|&G_i| Some(G_i)))
+                .chain(gens.H(padded_n).map(|&H_i| Some(H_i))),
+        )
+        .ok_or_else(|| R1CSError::VerificationError)?;
+
+        if !mega_check.is_identity() {
+            return Err(R1CSError::VerificationError);
+        }
+
+        Ok(())
+    }
+
+    /// Replays the proof on the transcript and computes the scalars
+    /// of the verification equation for the supplied `proof`.
+    fn verification_scalars(
+        &mut self,
+        proof: &R1CSProof,
+    ) -> Result<VerificationScalars, R1CSError> {
         // If the number of multiplications is not 0 or a power of 2, then pad the circuit.
-        let n = self.num_vars;
-        let padded_n = self.num_vars.next_power_of_two();
+        let n = self.state.num_vars;
+        let padded_n = self.state.num_vars.next_power_of_two();
         let pad = padded_n - n;
 
         use inner_product_proof::inner_product;
-        use std::iter;
         use util;
 
         if self.bp_gens.gens_capacity < padded_n {
             return Err(R1CSError::InvalidGeneratorsLength);
         }
-        // We are performing a single-party circuit proof, so party index is 0.
-        let gens = self.bp_gens.share(0);
 
         self.transcript.commit_point(b"A_I", &proof.A_I);
         self.transcript.commit_point(b"A_O", &proof.A_O);
@@ -308,21 +547,6 @@ impl<'a, 'b> VerifierCS<'a, 'b> {
 
         let delta = inner_product(&yneg_wR[0..n], &wL);
 
-        // define parameters for P check
-        let g_scalars = yneg_wR
-            .iter()
-            .zip(s.iter().take(padded_n))
-            .map(|(yneg_wRi, s_i)| x * yneg_wRi - a * s_i);
-
-        let h_scalars = y_inv_vec
-            .iter()
-            .zip(s.iter().rev().take(padded_n))
-            .zip(wL.into_iter().chain(iter::repeat(Scalar::zero()).take(pad)))
-            .zip(wO.into_iter().chain(iter::repeat(Scalar::zero()).take(pad)))
-            .map(|(((y_inv_i, s_i_inv), wLi), wOi)| {
-                y_inv_i * (x * wLi + wOi - b * s_i_inv) - Scalar::one()
-            });
-
         // Create a `TranscriptRng` from the transcript. The verifier
         // has no witness data to commit, so this just mixes external
         // randomness into the existing transcript.
@@ -330,48 +554,291 @@ impl<'a, 'b> VerifierCS<'a, 'b> {
         let mut rng = self.transcript.build_rng().finalize(&mut thread_rng());
         let r = Scalar::random(&mut rng);
 
+        Ok(VerificationScalars {
+            padded_n,
+            x,
+            w,
+            r,
+            a,
+            b,
+            t_x: proof.t_x,
+            t_x_blinding: proof.t_x_blinding,
+            e_blinding: proof.e_blinding,
+            wL,
+            wO,
+            wV,
+            wc,
+            delta,
+            u_sq,
+            u_inv_sq,
+            s,
+            y_inv_vec,
+            yneg_wR,
+            rng,
+        })
+    }
+
+    /// Consume this `VerifierCS` and compute the terms of the
+    /// verification equation for the supplied `proof`, for combining
+    /// it with the equations of other proofs.
+    fn verification_terms(mut self, proof: &R1CSProof) -> Result<VerificationTerms, R1CSError> {
+        let mut scalars = self.verification_scalars(proof)?;
+
+        Ok(VerificationTerms {
+            batch_weight: Scalar::random(&mut scalars.rng),
+            dynamic_scalars: scalars.dynamic_scalars().collect(),
+            dynamic_points: dynamic_points(proof, &self.V).collect(),
+            B_scalar: scalars.B_scalar(),
+            B_blinding_scalar: scalars.B_blinding_scalar(),
+            g_scalars: scalars.g_scalars().collect(),
+            h_scalars: scalars.h_scalars().collect(),
+        })
+    }
+}
+
+/// Returns the points of `proof` and the commitments `V`, in the
+/// order of [`VerificationScalars::dynamic_scalars`].
+fn dynamic_points<'p>(
+    proof: &'p R1CSProof,
+    V: &'p [CompressedRistretto],
+) -> impl Iterator<Item = Option<RistrettoPoint>> + 'p {
+    iter::once(proof.A_I.decompress())
+        .chain(iter::once(proof.A_O.decompress()))
+        .chain(iter::once(proof.S.decompress()))
+        .chain(V.iter().map(|V_i| V_i.decompress()))
+        .chain(iter::once(proof.T_1.decompress()))
+        .chain(iter::once(proof.T_3.decompress()))
+        .chain(iter::once(proof.T_4.decompress()))
+        .chain(iter::once(proof.T_5.decompress()))
+        .chain(iter::once(proof.T_6.decompress()))
+        .chain(proof.ipp_proof.L_vec.iter().map(|L_i| L_i.decompress()))
+        .chain(proof.ipp_proof.R_vec.iter().map(|R_i| R_i.decompress()))
+}
+
+/// The challenges and intermediate values of the verification
+/// equation of a single proof, from which the scalars of the
+/// equation are computed on the fly.
+struct VerificationScalars {
+    padded_n: usize,
+    x: Scalar,
+    w: Scalar,
+    r: Scalar,
+    a: Scalar,
+    b: Scalar,
+    t_x: Scalar,
+    t_x_blinding: Scalar,
+    e_blinding: Scalar,
+    wL: Vec<Scalar>,
+    wO: Vec<Scalar>,
+    wV: Vec<Scalar>,
+    wc: Scalar,
+    delta: Scalar,
+    u_sq: Vec<Scalar>,
+    u_inv_sq: Vec<Scalar>,
+    s: Vec<Scalar>,
+    y_inv_vec: Vec<Scalar>,
+    yneg_wR: Vec<Scalar>,
+    /// The verifier's RNG, for drawing a batch weight.
+    rng: TranscriptRng,
+}
+
+impl VerificationScalars {
+    /// Returns the scalars for the points returned by
+    /// [`dynamic_points`].
+    fn dynamic_scalars<'s>(&'s self) -> impl Iterator<Item = Scalar> + 's {
+        let x = self.x;
         let xx = x * x;
-        let rxx = r * xx;
+        let rxx = self.r * xx;
         let xxx = x * xx;
 
-        // group the T_scalars and T_points together
-        let T_scalars = [r * x, rxx * x, rxx * xx, rxx * xxx, rxx * xx * xx];
-        let T_points = [proof.T_1, proof.T_3, proof.T_4, proof.T_5, proof.T_6];
+        iter::once(x) // A_I
+            .chain(iter::once(xx)) // A_O
+            .chain(iter::once(xxx)) // S
+            .chain(self.wV.iter().map(move |wVi| wVi * rxx)) // V
+            .chain(iter::once(self.r * x)) // T_1
+            .chain(iter::once(rxx * x)) // T_3
+            .chain(iter::once(rxx * xx)) // T_4
+            .chain(iter::once(rxx * xxx)) // T_5
+            .chain(iter::once(rxx * xx * xx)) // T_6
+            .chain(self.u_sq.iter().cloned()) // ipp_proof.L_vec
+            .chain(self.u_inv_sq.iter().cloned()) // ipp_proof.R_vec
+    }
 
-        let mega_check = RistrettoPoint::optional_multiscalar_mul(
-            iter::once(x) // A_I
-                .chain(iter::once(xx)) // A_O
-                .chain(iter::once(xxx)) // S
-                .chain(wV.iter().map(|wVi| wVi * rxx)) // V
-                .chain(T_scalars.iter().cloned()) // T_points
-                .chain(iter::once(
-                    w * (proof.t_x - a * b) + r * (xx * (wc + delta) - proof.t_x),
-                )) // B
-                .chain(iter::once(-proof.e_blinding - r * proof.t_x_blinding)) // B_blinding
-                .chain(g_scalars) // G
-                .chain(h_scalars) // H
-                .chain(u_sq.iter().cloned()) // ipp_proof.L_vec
-                .chain(u_inv_sq.iter().cloned()), // ipp_proof.R_vec
-            iter::once(proof.A_I.decompress())
-                .chain(iter::once(proof.A_O.decompress()))
-                .chain(iter::once(proof.S.decompress()))
-                .chain(self.V.iter().map(|V_i| V_i.decompress()))
-                .chain(T_points.iter().map(|T_i| T_i.decompress()))
-                .chain(iter::once(Some(self.pc_gens.B)))
-                .chain(iter::once(Some(self.pc_gens.B_blinding)))
-                .chain(gens.G(padded_n).map(|&G_i| Some(G_i)))
-                .chain(gens.H(padded_n).map(|&H_i| Some(H_i)))
-                .chain(proof.ipp_proof.L_vec.iter().map(|L_i| L_i.decompress()))
-                .chain(proof.ipp_proof.R_vec.iter().map(|R_i| R_i.decompress())),
-        )
-        .ok_or_else(|| R1CSError::VerificationError)?;
+    /// Returns the scalar for the Pedersen generator \\(B\\).
+    fn B_scalar(&self) -> Scalar {
+        let xx = self.x * self.x;
+        self.w * (self.t_x - self.a * self.b) + self.r * (xx * (self.wc + self.delta) - self.t_x)
+    }
 
-        use curve25519_dalek::traits::IsIdentity;
+    /// Returns the scalar for the Pedersen generator
+    /// \\(\widetilde{B}\\).
+    fn B_blinding_scalar(&self) -> Scalar {
+        -self.e_blinding - self.r * self.t_x_blinding
+    }
 
-        if !mega_check.is_identity() {
-            return Err(R1CSError::VerificationError);
+    /// Returns the scalars for the first party's \\(\mathbf{G}\\)
+    /// generators.
+    fn g_scalars<'s>(&'s self) -> impl Iterator<Item = Scalar> + 's {
+        let (x, a) = (self.x, self.a);
+        self.yneg_wR
+            .iter()
+            .zip(self.s.iter().take(self.padded_n))
+            .map(move |(yneg_wRi, s_i)| x * yneg_wRi - a * s_i)
+    }
+
+    /// Returns the scalars for the first party's \\(\mathbf{H}\\)
+    /// generators.
+    fn h_scalars<'s>(&'s self) -> impl Iterator<Item = Scalar> + 's {
+        let (x, b) = (self.x, self.b);
+        let pad = self.padded_n - self.wL.len();
+        self.y_inv_vec
+            .iter()
+            .zip(self.s.iter().rev().take(self.padded_n))
+            .zip(
+                self.wL
+                    .iter()
+                    .cloned()
+                    .chain(iter::repeat(Scalar::zero()).take(pad)),
+            )
+            .zip(
+                self.wO
+                    .iter()
+                    .cloned()
+                    .chain(iter::repeat(Scalar::zero()).take(pad)),
+            )
+            .map(move |(((y_inv_i, s_i_inv), wLi), wOi)| {
+                y_inv_i * (x * wLi + wOi - b * s_i_inv) - Scalar::one()
+            })
+    }
+}
+
+/// The terms of the verification equation of a single proof, which
+/// holds if their multiscalar multiplication is the identity.
+///
+/// The scalars for the generators are kept separate from the ones for
+/// the points specific to the proof, so that the equations of several
+/// proofs can be combined into one.
+struct VerificationTerms {
+    /// A random weight for combining this equation with others.
+    batch_weight: Scalar,
+    /// Scalars for the `dynamic_points`.
+    dynamic_scalars: Vec<Scalar>,
+    /// The points of the proof and the commitments.
+    dynamic_points: Vec<Option<RistrettoPoint>>,
+    /// Scalar for the Pedersen generator \\(B\\).
+    B_scalar: Scalar,
+    /// Scalar for the Pedersen generator \\(\widetilde{B}\\).
+    B_blinding_scalar: Scalar,
+    /// Scalars for the first party's \\(\mathbf{G}\\) generators.
+    g_scalars: Vec<Scalar>,
+    /// Scalars for the first party's \\(\mathbf{H}\\) generators.
+    h_scalars: Vec<Scalar>,
+}
+
+/// Returns whether the first `n` generators of the first party of
+/// `bp_gens` and `pc_gens` are the same as the ones of `other`.
+///
+/// Generators that are the same instances are not compared point by
+/// point.
+fn same_generators(
+    (bp_gens, pc_gens): (&BulletproofGens, &PedersenGens),
+    (other_bp_gens, other_pc_gens): (&BulletproofGens, &PedersenGens),
+    n: usize,
+) -> bool {
+    if ptr::eq(bp_gens, other_bp_gens) && ptr::eq(pc_gens, other_pc_gens) {
+        return true;
+    }
+    let (gens, other_gens) = (bp_gens.share(0), other_bp_gens.share(0));
+    pc_gens.B == other_pc_gens.B
+        && pc_gens.B_blinding == other_pc_gens.B_blinding
+        && gens.G(n).eq(other_gens.G(n))
+        && gens.H(n).eq(other_gens.H(n))
+}
+
+/// Verifies a batch of proofs, each with the `VerifierCS` for its
+/// constraint system, with a single multiscalar multiplication.
+///
+/// Succeeds only if every proof would pass [`VerifierCS::verify`].
+/// The verification equations are combined using random weights
+/// drawn from each proof's transcript, mixed with external
+/// randomness, so a batch containing an invalid proof is rejected
+/// with overwhelming probability.  This does not identify which of
+/// the proofs is invalid.
+///
+/// The constraint systems should be created with the same
+/// `BulletproofGens` and `PedersenGens` instances.  Generators that
+/// are different instances are compared point by point, and
+/// [`R1CSError::GeneratorsMismatch`] is returned if they differ.
+pub fn batch_verify<'a, 'b, 'p, I>(instances: I) -> Result<(), R1CSError>
+where
+    I: IntoIterator<Item = (VerifierCS<'a, 'b>, &'p R1CSProof)>,
+{
+    let mut gens: Option<(&BulletproofGens, &PedersenGens)> = None;
+    let mut B_scalar = Scalar::zero();
+    let mut B_blinding_scalar = Scalar::zero();
+    let mut g_scalars: Vec<Scalar> = Vec::new();
+    let mut h_scalars: Vec<Scalar> = Vec::new();
+    let mut dynamic_scalars = Vec::new();
+    let mut dynamic_points = Vec::new();
+
+    for (cs, proof) in instances {
+        let cs_gens = (cs.bp_gens, cs.pc_gens);
+        let terms = cs.verification_terms(proof)?;
+        let padded_n = terms.g_scalars.len();
+
+        // The equations are combined using the generators of the
+        // first proof.
+        let gens = *gens.get_or_insert(cs_gens);
+        if gens.0.gens_capacity < padded_n {
+            return Err(R1CSError::InvalidGeneratorsLength);
+        }
+        if !same_generators(gens, cs_gens, padded_n) {
+            return Err(R1CSError::GeneratorsMismatch);
         }
 
-        Ok(())
+        let c = terms.batch_weight;
+
+        B_scalar += c * terms.B_scalar;
+        B_blinding_scalar += c * terms.B_blinding_scalar;
+        if g_scalars.len() < padded_n {
+            g_scalars.resize(padded_n, Scalar::zero());
+            h_scalars.resize(padded_n, Scalar::zero());
+        }
+        for (acc, g_i) in g_scalars.iter_mut().zip(terms.g_scalars.iter()) {
+            *acc += c * g_i;
+        }
+        for (acc, h_i) in h_scalars.iter_mut().zip(terms.h_scalars.iter()) {
+            *acc += c * h_i;
+        }
+        dynamic_scalars.extend(terms.dynamic_scalars.iter().map(|s_i| c * s_i));
+        dynamic_points.extend(terms.dynamic_points);
     }
+
+    let (bp_gens, pc_gens) = match gens {
+        Some(gens) => gens,
+        None => return Ok(()),
+    };
+    let gens = bp_gens.share(0);
+    let padded_n = g_scalars.len();
+    let mega_check = RistrettoPoint::optional_multiscalar_mul(
+        dynamic_scalars
+            .into_iter()
+            .chain(iter::once(B_scalar))
+            .chain(iter::once(B_blinding_scalar))
+            .chain(g_scalars)
+            .chain(h_scalars),
+        dynamic_points
+            .into_iter()
+            .chain(iter::once(Some(pc_gens.B)))
+            .chain(iter::once(Some(pc_gens.B_blinding)))
+            .chain(gens.G(padded_n).map(|&G_i| Some(G_i)))
+            .chain(gens.H(padded_n).map(|&H_i| Some(H_i))),
+    )
+    .ok_or_else(|| R1CSError::VerificationError)?;
+
+    if !mega_check.is_identity() {
+        return Err(R1CSError::VerificationError);
+    }
+
+    Ok(())
 }
@@ -0,0 +1,265 @@
+//! The `blinding` module contains the API for parties that want to
+//! jointly generate a blinding factor for a shared commitment.
+//!
+//! Each party samples a local blinding share and publishes a
+//! [`MultiPartyBlinding`] commitment to it.  Once all commitments have
+//! been exchanged, the parties reveal their shares, which are summed
+//! by [`MultiPartyBlinding::combine`] into the joint blinding factor.
+//!
+//! # Security assumptions
+//!
+//! The joint blinding factor is uniformly random as long as **at
+//! least one** party samples its share with an honest RNG and that
+//! share is independent of the other parties' shares.  The
+//! commitments must be exchanged *before* any share is revealed, so
+//! that a party who speaks last cannot choose its share as a function
+//! of the others' and bias the sum.  Anyone holding the commitments
+//! can check the revealed shares with
+//! [`MultiPartyBlinding::combine_commitments`].
+//!
+//! Alternatively, a dealer who knows a blinding factor can split it
+//! into a `t`-of-`n` Shamir sharing with [`ThresholdBlinding::split`],
+//! so that any `t` of the [`BlindingShare`]s reconstruct it and fewer
+//! reveal nothing about it.
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::{CryptoRng, RngCore};
+
+use errors::ProofError;
+use generators::PedersenGens;
+
+/// A commitment \\(r\_j \cdot \widetilde{B}\\) to a party's share
+/// \\(r\_j\\) of a jointly generated blinding factor.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct MultiPartyBlinding {
+    commitment: RistrettoPoint,
+}
+
+impl MultiPartyBlinding {
+    /// Samples a local blinding share using `rng`.
+    ///
+    /// Returns the commitment to the share, which should be sent to
+    /// the other parties, and the secret share itself, which should be
+    /// kept until all commitments have been received.
+    pub fn new<R: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        rng: &mut R,
+    ) -> (MultiPartyBlinding, Scalar) {
+        let share = Scalar::random(rng);
+        let commitment = share * pc_gens.B_blinding;
+
+        (MultiPartyBlinding { commitment }, share)
+    }
+
+    /// Returns the commitment to the party's blinding share.
+    pub fn commitment(&self) -> RistrettoPoint {
+        self.commitment
+    }
+
+    /// Sums the parties' revealed `shares` into the joint blinding factor.
+    pub fn combine(shares: &[Scalar]) -> Scalar {
+        shares.iter().sum()
+    }
+
+    /// Sums the parties' commitments into a commitment to the joint
+    /// blinding factor.
+    ///
+    /// The result equals `MultiPartyBlinding::combine(shares) * B_blinding`
+    /// if every party revealed the share it committed to.
+    pub fn combine_commitments(commitments: &[MultiPartyBlinding]) -> RistrettoPoint {
+        commitments.iter().map(|c| c.commitment).sum()
+    }
+}
+
+/// A party's share \\(f(i)\\) of a blinding factor split with
+/// [`ThresholdBlinding::split`].
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct BlindingShare {
+    index: u32,
+    value: Scalar,
+}
+
+impl BlindingShare {
+    /// Returns the evaluation point \\(i \geq 1\\) of the share.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns the share's value \\(f(i)\\).
+    pub fn value(&self) -> Scalar {
+        self.value
+    }
+}
+
+/// Shamir secret sharing of a blinding factor over the scalar field.
+///
+/// The blinding factor \\(\tilde{v}\\) is the constant term of a
+/// random polynomial \\(f\\) of degree \\(t - 1\\), and the \\(i\\)-th
+/// party receives \\(f(i)\\).  Note that whoever creates the proof
+/// must reconstruct \\(\tilde{v}\\), so the sharing only protects the
+/// blinding factor while it is at rest.
+pub struct ThresholdBlinding;
+
+impl ThresholdBlinding {
+    /// Splits `blinding` into `n` shares, any `t` of which suffice to
+    /// reconstruct it.
+    ///
+    /// Panics if `t` is zero or greater than `n`.
+    pub fn split<R: RngCore + CryptoRng>(
+        blinding: Scalar,
+        t: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Vec<BlindingShare> {
+        assert!(
+            t >= 1 && t <= n,
+            "ThresholdBlinding::split: need 1 <= t <= n"
+        );
+        assert!(n <= u32::max_value() as usize);
+
+        let coeffs: Vec<Scalar> = (1..t).map(|_| Scalar::random(rng)).collect();
+
+        (1..=n as u32)
+            .map(|index| {
+                let x = Scalar::from(index);
+                // Evaluate f(x) using Horner's rule
+                let value = coeffs
+                    .iter()
+                    .rev()
+                    .fold(Scalar::zero(), |acc, c| acc * x + c)
+                    * x
+                    + blinding;
+                BlindingShare { index, value }
+            })
+            .collect()
+    }
+
+    /// Reconstructs the blinding factor from `shares` by Lagrange
+    /// interpolation of \\(f(0)\\), where `t` is the threshold the
+    /// blinding factor was split with.
+    ///
+    /// Returns [`ProofError::InvalidBlindingShares`] if fewer than `t`
+    /// shares are given, since the result would then be unrelated to
+    /// the blinding factor, or if a share has index zero or two
+    /// shares have the same index.
+    pub fn reconstruct(shares: &[BlindingShare], t: usize) -> Result<Scalar, ProofError> {
+        if shares.len() < t || shares.iter().any(|s| s.index == 0) {
+            return Err(ProofError::InvalidBlindingShares);
+        }
+        let xs: Vec<Scalar> = shares.iter().map(|s| Scalar::from(s.index)).collect();
+
+        let mut denominators: Vec<Scalar> = xs
+            .iter()
+            .enumerate()
+            .map(|(i, x_i)| {
+                xs.iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, x_j)| x_j - x_i)
+                    .product()
+            })
+            .collect();
+        // A zero denominator means that two shares have the same index.
+        if denominators.iter().any(|d| d == &Scalar::zero()) {
+            return Err(ProofError::InvalidBlindingShares);
+        }
+        Scalar::batch_invert(&mut denominators);
+
+        let x_product: Scalar = xs.iter().product();
+
+        Ok(shares
+            .iter()
+            .zip(xs.iter().zip(denominators.iter()))
+            .map(|(share, (x_i, denominator_inv))| {
+                // lambda_i = prod_{j != i} x_j / (x_j - x_i)
+                share.value * x_product * x_i.invert() * denominator_inv
+            })
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combined_blinding_matches_commitments() {
+        let pc_gens = PedersenGens::default();
+        let mut rng = rand::thread_rng();
+
+        let (commitments, shares): (Vec<_>, Vec<_>) = (0..4)
+            .map(|_| MultiPartyBlinding::new(&pc_gens, &mut rng))
+            .unzip();
+
+        let blinding = MultiPartyBlinding::combine(&shares);
+
+        assert_eq!(blinding, shares[0] + shares[1] + shares[2] + shares[3]);
+        assert_eq!(
+            MultiPartyBlinding::combine_commitments(&commitments),
+            blinding * pc_gens.B_blinding
+        );
+
+        // A commitment to the joint blinding factor opens the same way
+        // as one built from the sum of the parties' commitments.
+        let value = Scalar::from(42u64);
+        assert_eq!(
+            pc_gens.commit(value, blinding),
+            value * pc_gens.B + MultiPartyBlinding::combine_commitments(&commitments)
+        );
+    }
+
+    #[test]
+    fn threshold_blinding_reconstructs() {
+        let mut rng = rand::thread_rng();
+        let blinding = Scalar::random(&mut rng);
+
+        let shares = ThresholdBlinding::split(blinding, 3, 5, &mut rng);
+        assert_eq!(shares.len(), 5);
+
+        assert_eq!(ThresholdBlinding::reconstruct(&shares, 3), Ok(blinding));
+        assert_eq!(
+            ThresholdBlinding::reconstruct(&shares[..3], 3),
+            Ok(blinding)
+        );
+        assert_eq!(
+            ThresholdBlinding::reconstruct(&[shares[4], shares[0], shares[2]], 3),
+            Ok(blinding)
+        );
+
+        let shares = ThresholdBlinding::split(blinding, 1, 2, &mut rng);
+        assert_eq!(shares[0].value(), blinding);
+        assert_eq!(
+            ThresholdBlinding::reconstruct(&shares[1..], 1),
+            Ok(blinding)
+        );
+    }
+
+    #[test]
+    fn threshold_blinding_rejects_invalid_shares() {
+        let mut rng = rand::thread_rng();
+        let blinding = Scalar::random(&mut rng);
+        let shares = ThresholdBlinding::split(blinding, 3, 5, &mut rng);
+
+        // Fewer than t shares would reconstruct an unrelated scalar.
+        assert_eq!(
+            ThresholdBlinding::reconstruct(&shares[..2], 3),
+            Err(ProofError::InvalidBlindingShares)
+        );
+        assert_eq!(
+            ThresholdBlinding::reconstruct(&[shares[0], shares[1], shares[1]], 3),
+            Err(ProofError::InvalidBlindingShares)
+        );
+
+        // Shares with index zero cannot come from a split, but can be
+        // deserialized.
+        let zero = BlindingShare {
+            index: 0,
+            value: blinding,
+        };
+        assert_eq!(
+            ThresholdBlinding::reconstruct(&[zero, shares[0], shares[1]], 3),
+            Err(ProofError::InvalidBlindingShares)
+        );
+    }
+}
@@ -3,8 +3,13 @@
 
 use rand;
 
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
 use std::iter;
+use std::str::FromStr;
 
+use clear_on_drop::clear::Clear;
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
@@ -13,18 +18,53 @@ use merlin::Transcript;
 use errors::ProofError;
 use generators::{BulletproofGens, PedersenGens};
 use inner_product_proof::InnerProductProof;
+use proof_system::ProofSystem;
 use transcript::TranscriptProtocol;
 use util;
+use verification_equation::VerificationEquation;
+
+use self::blinding::{BlindingShare, ThresholdBlinding};
 
 use serde::de::Visitor;
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
 
 // Modules for MPC protocol
 
+pub mod blinding;
 pub mod dealer;
 pub mod messages;
 pub mod party;
 
+/// The maximum bitsize \\(n\\) of a range proof.
+///
+/// Range proofs are supported for \\(n = 8, 16, 32, 64\\); use
+/// [`BulletproofGens::max_range_bits`] to find the largest of these
+/// supported by a particular generator set.
+pub const RANGE_PROOF_MAX_BITS: usize = 64;
+
+/// Durations of the phases of creating a range proof, in microseconds,
+/// as returned by [`RangeProof::prove_single_timed`].
+#[cfg(feature = "timing")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ProofTimings {
+    /// Time to compute the bit and polynomial commitments and the
+    /// parties' proof shares.
+    pub commitment_us: u64,
+    /// Time to assemble the proof shares, including the inner-product
+    /// argument.
+    pub inner_product_us: u64,
+    /// Time to serialize the proof.
+    pub serialization_us: u64,
+    /// Length in bytes of the serialized proof.
+    pub serialized_len: usize,
+}
+
+/// Returns the duration `d` in whole microseconds.
+#[cfg(feature = "timing")]
+fn as_micros(d: ::std::time::Duration) -> u64 {
+    d.as_secs() * 1_000_000 + u64::from(d.subsec_micros())
+}
+
 /// The `RangeProof` struct represents a proof that one or more values
 /// are in a range.
 ///
@@ -73,6 +113,9 @@ impl RangeProof {
     /// blinding scalar `v_blinding`.
     /// This is a convenience wrapper around [`RangeProof::prove_multiple`].
     ///
+    /// Returns [`ProofError::InvalidBitsize`] if `n` exceeds
+    /// [`RANGE_PROOF_MAX_BITS`].
+    ///
     /// # Example
     /// ```
     /// extern crate rand;
@@ -138,6 +181,115 @@ impl RangeProof {
         Ok((p, Vs[0]))
     }
 
+    /// Create a rangeproof for a value `v` whose blinding factor is
+    /// held as a [`ThresholdBlinding`] sharing with threshold `t`.
+    ///
+    /// The blinding factor is reconstructed from `shares`, and cleared
+    /// once the proof is created.  Returns
+    /// [`ProofError::InvalidBlindingShares`] if there are fewer than
+    /// `t` shares or their indices are invalid, see
+    /// [`ThresholdBlinding::reconstruct`].  Otherwise behaves as
+    /// [`RangeProof::prove_single`].
+    pub fn prove_single_with_shared_blinding(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        shares: &[BlindingShare],
+        t: usize,
+        n: usize,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        let mut v_blinding = ThresholdBlinding::reconstruct(shares, t)?;
+        let result = RangeProof::prove_single(bp_gens, pc_gens, transcript, v, &v_blinding, n);
+        v_blinding.clear();
+        result
+    }
+
+    /// Create a rangeproof for a value `v` whose commitment was
+    /// computed elsewhere, e.g. by a hardware wallet.
+    ///
+    /// Returns [`ProofError::CommitmentMismatch`] if `commitment` is
+    /// not \\(v \cdot B + \tilde{v} \cdot \widetilde{B}\\) for the given
+    /// `v` and `v_blinding`, so that a proof is never produced for
+    /// the wrong commitment.  Otherwise behaves as
+    /// [`RangeProof::prove_single`].
+    pub fn prove_with_commitment(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        commitment: &CompressedRistretto,
+        n: usize,
+    ) -> Result<RangeProof, ProofError> {
+        if RangeProof::compressed_commitment_to_value(pc_gens, v, v_blinding) != *commitment {
+            return Err(ProofError::CommitmentMismatch);
+        }
+        let (proof, _) = RangeProof::prove_single(bp_gens, pc_gens, transcript, v, v_blinding, n)?;
+        Ok(proof)
+    }
+
+    /// Create a rangeproof for a value `v` bound to a caller-supplied
+    /// `label`, such as a payment identifier.
+    ///
+    /// The label and the commitment to `v` are committed to the
+    /// transcript before the proof is created, so the proof only
+    /// verifies with
+    /// [`RangeProof::verify_single_with_commitment_binding`] for the
+    /// same label and commitment.  Otherwise behaves as
+    /// [`RangeProof::prove_single`].
+    pub fn prove_single_with_commitment_binding(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        label: &[u8],
+        n: usize,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        let commitment = pc_gens.commit(Scalar::from(v), *v_blinding).compress();
+        transcript.commitment_binding(label, &commitment);
+        RangeProof::prove_single(bp_gens, pc_gens, transcript, v, v_blinding, n)
+    }
+
+    /// Create a rangeproof that a value `v` lies in the public range
+    /// \\([lo, hi)\\), which need not be of the form \\([0, 2^n)\\).
+    ///
+    /// This is an aggregated proof of the two statements
+    /// \\(v - lo \in [0, 2^n)\\) and \\(hi - 1 - v \in [0, 2^n)\\),
+    /// where \\(n\\) is the smallest supported bitsize such that
+    /// \\(2^n \geq hi - lo\\), so `bp_gens` must have a party capacity
+    /// of at least 2.  Both statements are proved with commitments
+    /// derived from the commitment to `v`, which is returned along
+    /// with the proof.
+    ///
+    /// Returns [`ProofError::InvalidBitsize`] if the range is empty.
+    /// As with [`RangeProof::prove_single`], a value outside the range
+    /// produces a proof that fails verification.
+    pub fn prove_range(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        lo: u64,
+        hi: u64,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        let n = range_bitsize(lo, hi).ok_or(ProofError::InvalidBitsize)?;
+        let (proof, _) = RangeProof::prove_multiple(
+            bp_gens,
+            pc_gens,
+            transcript,
+            &[v.wrapping_sub(lo), (hi - 1).wrapping_sub(v)],
+            &[*v_blinding, -v_blinding],
+            n,
+        )?;
+        Ok((
+            proof,
+            pc_gens.commit(Scalar::from(v), *v_blinding).compress(),
+        ))
+    }
+
     /// Create a rangeproof for a set of values.
     ///
     /// # Example
@@ -200,6 +352,71 @@ impl RangeProof {
         blindings: &[Scalar],
         n: usize,
     ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        let (dealer, proof_shares, value_commitments) =
+            RangeProof::commit_multiple(bp_gens, pc_gens, transcript, values, blindings, n)?;
+
+        let proof = dealer.receive_trusted_shares(&proof_shares)?;
+
+        Ok((proof, value_commitments))
+    }
+
+    /// Create an aggregated rangeproof for the `output_values` of a
+    /// transaction spending `input_value`, such that the outputs
+    /// balance the input.
+    ///
+    /// The output blindings must sum to `input_blinding`, so that the
+    /// sum of the returned output commitments equals the commitment
+    /// \\(V\_{in}\\) to the input.  The balance is then checked by
+    /// [`RangeProof::verify_split`] using the homomorphic property of
+    /// the commitments, while the rangeproof ensures that no output
+    /// is negative.  As with [`RangeProof::prove_multiple`], the number
+    /// of outputs must be a power of two.
+    ///
+    /// Returns [`ProofError::CommitmentMismatch`] if the output values
+    /// or blindings do not sum to the input value or blinding.
+    pub fn prove_split(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        input_value: u64,
+        input_blinding: &Scalar,
+        output_values: &[u64],
+        output_blindings: &[Scalar],
+        n: usize,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        let output_sum: u128 = output_values.iter().map(|&v| u128::from(v)).sum();
+        let blinding_sum: Scalar = output_blindings.iter().sum();
+        if output_sum != u128::from(input_value) || blinding_sum != *input_blinding {
+            return Err(ProofError::CommitmentMismatch);
+        }
+        RangeProof::prove_multiple(
+            bp_gens,
+            pc_gens,
+            transcript,
+            output_values,
+            output_blindings,
+            n,
+        )
+    }
+
+    /// Runs the MPC protocol locally up to the point where the dealer
+    /// has all the proof shares, returning the dealer, the shares and
+    /// the value commitments.
+    fn commit_multiple<'a, 'b>(
+        bp_gens: &'b BulletproofGens,
+        pc_gens: &'b PedersenGens,
+        transcript: &'a mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: usize,
+    ) -> Result<
+        (
+            dealer::DealerAwaitingProofShares<'a, 'b>,
+            Vec<messages::ProofShare>,
+            Vec<CompressedRistretto>,
+        ),
+        ProofError,
+    > {
         use self::dealer::*;
         use self::party::*;
 
@@ -242,9 +459,46 @@ impl RangeProof {
             // Collect the iterator of Results into a Result<Vec>, then unwrap it
             .collect::<Result<Vec<_>, _>>()?;
 
+        Ok((dealer, proof_shares, value_commitments))
+    }
+
+    /// Create a rangeproof for a given pair of value `v` and
+    /// blinding scalar `v_blinding`, recording how long each phase
+    /// of proving takes.
+    ///
+    /// This behaves as [`RangeProof::prove_single`], and additionally
+    /// returns the [`ProofTimings`] of the commitment phase, the
+    /// inner-product argument, and the serialization of the proof.
+    /// The proof is serialized only to measure the time it takes and
+    /// the length of its encoding.
+    #[cfg(feature = "timing")]
+    pub fn prove_single_timed(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        n: usize,
+    ) -> Result<(RangeProof, CompressedRistretto, ProofTimings), ProofError> {
+        use std::time::Instant;
+
+        let start = Instant::now();
+        let (dealer, proof_shares, Vs) =
+            RangeProof::commit_multiple(bp_gens, pc_gens, transcript, &[v], &[*v_blinding], n)?;
+        let committed = Instant::now();
         let proof = dealer.receive_trusted_shares(&proof_shares)?;
+        let proved = Instant::now();
+        let bytes = proof.to_bytes();
+        let serialized = Instant::now();
+
+        let timings = ProofTimings {
+            commitment_us: as_micros(committed - start),
+            inner_product_us: as_micros(proved - committed),
+            serialization_us: as_micros(serialized - proved),
+            serialized_len: bytes.len(),
+        };
 
-        Ok((proof, value_commitments))
+        Ok((proof, Vs[0], timings))
     }
 
     /// Verifies a rangeproof for a given value commitment \\(V\\).
@@ -261,6 +515,92 @@ impl RangeProof {
         self.verify_multiple(bp_gens, pc_gens, transcript, &[*V], n)
     }
 
+    /// Verifies a rangeproof created by
+    /// [`RangeProof::prove_single_with_commitment_binding`] for the
+    /// value commitment \\(V\\) and the given `label`.
+    pub fn verify_single_with_commitment_binding(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        label: &[u8],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        transcript.commitment_binding(label, V);
+        self.verify_single(bp_gens, pc_gens, transcript, V, n)
+    }
+
+    /// Verifies a rangeproof created by [`RangeProof::prove_range`]
+    /// that the value committed to by \\(V\\) lies in \\([lo, hi)\\).
+    pub fn verify_range(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        lo: u64,
+        hi: u64,
+    ) -> Result<(), ProofError> {
+        let n = range_bitsize(lo, hi).ok_or(ProofError::InvalidBitsize)?;
+        let V = V.decompress().ok_or(ProofError::VerificationError)?;
+
+        // Commitments to v - lo and hi - 1 - v, with blindings
+        // v_blinding and -v_blinding respectively.
+        let V_lo = V - Scalar::from(lo) * pc_gens.B;
+        let V_hi = Scalar::from(hi - 1) * pc_gens.B - V;
+        self.verify_multiple(
+            bp_gens,
+            pc_gens,
+            transcript,
+            &[V_lo.compress(), V_hi.compress()],
+            n,
+        )
+    }
+
+    /// Verifies a rangeproof created by [`RangeProof::prove_split`],
+    /// checking that the `output_commitments` sum to the
+    /// `input_commitment` and that each output is in \\([0, 2^n)\\).
+    ///
+    /// Since each output is less than \\(2^n\\), their sum cannot
+    /// wrap around modulo the group order, so the outputs balance the
+    /// input as integers.
+    pub fn verify_split(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        input_commitment: &CompressedRistretto,
+        output_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        let input = input_commitment
+            .decompress()
+            .ok_or(ProofError::VerificationError)?;
+        let outputs = output_commitments
+            .iter()
+            .map(|V| V.decompress())
+            .collect::<Option<Vec<RistrettoPoint>>>()
+            .ok_or(ProofError::VerificationError)?;
+        if outputs.iter().sum::<RistrettoPoint>() != input {
+            return Err(ProofError::VerificationError);
+        }
+        self.verify_multiple(bp_gens, pc_gens, transcript, output_commitments, n)
+    }
+
+    /// Bundles the proof with its value `commitments`, as returned by
+    /// [`RangeProof::prove_multiple`], so that the two cannot get out
+    /// of sync.
+    pub fn store_commitments(
+        self,
+        commitments: Vec<CompressedRistretto>,
+    ) -> RangeProofWithCommitments {
+        RangeProofWithCommitments {
+            proof: self,
+            commitments,
+        }
+    }
+
     /// Verifies an aggregated rangeproof for the given value commitments.
     pub fn verify_multiple(
         &self,
@@ -270,6 +610,59 @@ impl RangeProof {
         value_commitments: &[CompressedRistretto],
         n: usize,
     ) -> Result<(), ProofError> {
+        let scalars = self.verification_scalars(bp_gens, transcript, value_commitments, n)?;
+
+        let mega_check = RistrettoPoint::optional_multiscalar_mul(
+            scalars.scalars(),
+            self.verification_points(bp_gens, pc_gens, value_commitments, n),
+        )
+        .ok_or_else(|| util::invalid_point_error(self.encoded_points()))?;
+
+        if mega_check.is_identity() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Replays the verifier's transcript for the given value
+    /// commitments and returns the final [`VerificationEquation`],
+    /// without evaluating it.
+    ///
+    /// The proof is valid if and only if the equation holds.  Returns
+    /// an error in the same cases as [`RangeProof::verify_multiple`]
+    /// before evaluating its final check, including if the proof or
+    /// the commitments contain invalid points.
+    pub fn verification_equation(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<VerificationEquation, ProofError> {
+        let scalars = self.verification_scalars(bp_gens, transcript, value_commitments, n)?;
+        let points: Option<Vec<RistrettoPoint>> = self
+            .verification_points(bp_gens, pc_gens, value_commitments, n)
+            .collect();
+        let points = points.ok_or_else(|| util::invalid_point_error(self.encoded_points()))?;
+
+        Ok(VerificationEquation::new(
+            scalars.scalars().collect(),
+            points,
+        ))
+    }
+
+    /// Replays the verifier's transcript for the given value
+    /// commitments and computes the challenges of the verification
+    /// equation.
+    fn verification_scalars(
+        &self,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<VerificationScalars, ProofError> {
         let m = value_commitments.len();
 
         // First, replay the "interactive" protocol using the proof
@@ -294,8 +687,6 @@ impl RangeProof {
 
         let y = transcript.challenge_scalar(b"y");
         let z = transcript.challenge_scalar(b"z");
-        let zz = z * z;
-        let minus_z = -z;
 
         transcript.commit_point(b"T_1", &self.T_1);
         transcript.commit_point(b"T_2", &self.T_2);
@@ -314,7 +705,6 @@ impl RangeProof {
         let c = Scalar::random(&mut rng);
 
         let (x_sq, x_inv_sq, s) = self.ipp_proof.verification_scalars(n * m, transcript)?;
-        let s_inv = s.iter().rev();
 
         let a = self.ipp_proof.a;
         let b = self.ipp_proof.b;
@@ -327,46 +717,105 @@ impl RangeProof {
             .flat_map(|exp_z| powers_of_2.iter().map(move |exp_2| exp_2 * exp_z))
             .collect();
 
-        let g = s.iter().map(|s_i| minus_z - a * s_i);
-        let h = s_inv
-            .zip(util::exp_iter(y.invert()))
-            .zip(concat_z_and_2.iter())
-            .map(|((s_i_inv, exp_y_inv), z_and_2)| z + exp_y_inv * (zz * z_and_2 - b * s_i_inv));
+        Ok(VerificationScalars {
+            m,
+            x,
+            y,
+            z,
+            c,
+            a,
+            b,
+            basepoint_scalar: w * (self.t_x - a * b) + c * (delta(n, m, &y, &z) - self.t_x),
+            blinding_scalar: -self.e_blinding - c * self.t_x_blinding,
+            x_sq,
+            x_inv_sq,
+            s,
+            concat_z_and_2,
+        })
+    }
 
-        let value_commitment_scalars = util::exp_iter(z).take(m).map(|z_exp| c * zz * z_exp);
-        let basepoint_scalar = w * (self.t_x - a * b) + c * (delta(n, m, &y, &z) - self.t_x);
+    /// Returns the points of the verification equation, in the order
+    /// of [`VerificationScalars::scalars`], or `None` for the ones
+    /// that fail to decompress.
+    fn verification_points<'a>(
+        &'a self,
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        value_commitments: &'a [CompressedRistretto],
+        n: usize,
+    ) -> impl Iterator<Item = Option<RistrettoPoint>> + 'a {
+        let m = value_commitments.len();
+        iter::once(self.A.decompress())
+            .chain(iter::once(self.S.decompress()))
+            .chain(iter::once(self.T_1.decompress()))
+            .chain(iter::once(self.T_2.decompress()))
+            .chain(self.ipp_proof.L_vec.iter().map(|L| L.decompress()))
+            .chain(self.ipp_proof.R_vec.iter().map(|R| R.decompress()))
+            .chain(iter::once(Some(pc_gens.B_blinding)))
+            .chain(iter::once(Some(pc_gens.B)))
+            .chain(bp_gens.G(n, m).map(|&x| Some(x)))
+            .chain(bp_gens.H(n, m).map(|&x| Some(x)))
+            .chain(value_commitments.iter().map(|V| V.decompress()))
+    }
 
-        let mega_check = RistrettoPoint::optional_multiscalar_mul(
-            iter::once(Scalar::one())
-                .chain(iter::once(x))
-                .chain(iter::once(c * x))
-                .chain(iter::once(c * x * x))
-                .chain(x_sq.iter().cloned())
-                .chain(x_inv_sq.iter().cloned())
-                .chain(iter::once(-self.e_blinding - c * self.t_x_blinding))
-                .chain(iter::once(basepoint_scalar))
-                .chain(g)
-                .chain(h)
-                .chain(value_commitment_scalars),
-            iter::once(self.A.decompress())
-                .chain(iter::once(self.S.decompress()))
-                .chain(iter::once(self.T_1.decompress()))
-                .chain(iter::once(self.T_2.decompress()))
-                .chain(self.ipp_proof.L_vec.iter().map(|L| L.decompress()))
-                .chain(self.ipp_proof.R_vec.iter().map(|R| R.decompress()))
-                .chain(iter::once(Some(pc_gens.B_blinding)))
-                .chain(iter::once(Some(pc_gens.B)))
-                .chain(bp_gens.G(n, m).map(|&x| Some(x)))
-                .chain(bp_gens.H(n, m).map(|&x| Some(x)))
-                .chain(value_commitments.iter().map(|V| V.decompress())),
+    /// Returns the points of the proof with their offsets in the
+    /// encoding, field names and indices, for
+    /// [`util::invalid_point_error`].
+    fn encoded_points(&self) -> impl Iterator<Item = util::EncodedPoint<'_>> {
+        vec![
+            (0, "A", None, &self.A),
+            (32, "S", None, &self.S),
+            (2 * 32, "T_1", None, &self.T_1),
+            (3 * 32, "T_2", None, &self.T_2),
+        ]
+        .into_iter()
+        .chain(
+            self.ipp_proof
+                .encoded_points()
+                .map(|(offset, field, index, point)| (7 * 32 + offset, field, index, point)),
         )
-        .ok_or_else(|| ProofError::VerificationError)?;
+    }
 
-        if mega_check.is_identity() {
-            Ok(())
-        } else {
-            Err(ProofError::VerificationError)
-        }
+    /// Returns the commitment \\(V = v \cdot B + \tilde{v} \cdot \widetilde{B}\\)
+    /// to `value` with blinding factor `blinding`, as created by the
+    /// provers, where \\(B\\) and \\(\widetilde{B}\\) are the `B` and
+    /// `B_blinding` generators of `pc_gens`.
+    ///
+    /// This is [`PedersenGens::commit`] for the scalar `Scalar::from(value)`.
+    pub fn commitment_to_value(
+        pc_gens: &PedersenGens,
+        value: u64,
+        blinding: &Scalar,
+    ) -> RistrettoPoint {
+        pc_gens.commit(Scalar::from(value), *blinding)
+    }
+
+    /// Returns the compressed commitment to `value`, as returned by
+    /// the provers and passed to the verifiers; see
+    /// [`RangeProof::commitment_to_value`].
+    pub fn compressed_commitment_to_value(
+        pc_gens: &PedersenGens,
+        value: u64,
+        blinding: &Scalar,
+    ) -> CompressedRistretto {
+        RangeProof::commitment_to_value(pc_gens, value, blinding).compress()
+    }
+
+    /// Returns the inner-product proof for the vectors
+    /// \\(\mathbf{l}(x), \mathbf{r}(x)\\) of length \\(n \cdot m\\).
+    pub fn inner_product_proof(&self) -> &InnerProductProof {
+        &self.ipp_proof
+    }
+
+    /// Returns the size in bytes of the serialization of an
+    /// aggregated proof for `m` values of `n` bits each, without
+    /// creating it.
+    ///
+    /// This is \\(32 \cdot (2 \lg (nm) + 9)\\) bytes, for \\(n\\) and
+    /// \\(m\\) powers of two.
+    pub fn size_in_bytes(n: usize, m: usize) -> usize {
+        // 7 elements: points A, S, T1, T2, scalars tx, tx_bl, e_bl.
+        7 * 32 + InnerProductProof::size_in_bytes(n * m)
     }
 
     /// Serializes the proof into a byte array of \\(2 \lg n + 9\\)
@@ -395,9 +844,27 @@ impl RangeProof {
         buf
     }
 
+    /// Serializes the proof as [`RangeProof::to_bytes`], after
+    /// checking that all of its points are valid.
+    ///
+    /// Returns a [`ProofError::InvalidPoint`] for the first point that
+    /// does not decompress, such as one parsed by
+    /// [`RangeProof::from_bytes`] from a corrupted encoding.
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>, ProofError> {
+        match util::find_invalid_point(self.encoded_points()) {
+            Some(e) => Err(e),
+            None => Ok(self.to_bytes()),
+        }
+    }
+
     /// Deserializes the proof from a byte slice.
     ///
     /// Returns an error if the byte slice cannot be parsed into a `RangeProof`.
+    ///
+    /// The points are not decompressed here but during verification,
+    /// which returns a [`ProofError::InvalidPoint`] giving the field
+    /// and the byte offset of a point that is not a valid compressed
+    /// Ristretto point.
     pub fn from_bytes(slice: &[u8]) -> Result<RangeProof, ProofError> {
         if slice.len() % 32 != 0 {
             return Err(ProofError::FormatError);
@@ -406,12 +873,12 @@ impl RangeProof {
             return Err(ProofError::FormatError);
         }
 
-        use util::read32;
+        use util::{read32, read_point};
 
-        let A = CompressedRistretto(read32(&slice[0 * 32..]));
-        let S = CompressedRistretto(read32(&slice[1 * 32..]));
-        let T_1 = CompressedRistretto(read32(&slice[2 * 32..]));
-        let T_2 = CompressedRistretto(read32(&slice[3 * 32..]));
+        let A = read_point(slice, 0);
+        let S = read_point(slice, 1 * 32);
+        let T_1 = read_point(slice, 2 * 32);
+        let T_2 = read_point(slice, 3 * 32);
 
         let t_x = Scalar::from_canonical_bytes(read32(&slice[4 * 32..]))
             .ok_or(ProofError::FormatError)?;
@@ -433,6 +900,80 @@ impl RangeProof {
             ipp_proof,
         })
     }
+
+    /// Writes the proof to `w`, as a one-byte header giving
+    /// \\(\lg (nm)\\), the number of rounds of the inner-product
+    /// argument, followed by the encoding given by
+    /// [`RangeProof::to_bytes`].
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> Result<(), ProofError> {
+        w.write_all(&[self.ipp_proof.depth() as u8])?;
+        w.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Reads a proof written by [`RangeProof::write_to`] from `r`.
+    ///
+    /// Exactly the header and the [`RangeProof::size_in_bytes`] bytes
+    /// of the proof it announces are read, so several proofs can be
+    /// read from one stream.  I/O errors, including a stream that ends
+    /// early, are reported as [`ProofError::IoError`], and malformed
+    /// encodings as in [`RangeProof::from_bytes`].
+    pub fn read_from<R: io::Read>(r: &mut R) -> Result<RangeProof, ProofError> {
+        let mut lg_nm = [0u8; 1];
+        r.read_exact(&mut lg_nm)?;
+        if lg_nm[0] >= 32 {
+            return Err(ProofError::FormatError);
+        }
+        let mut buf = vec![0u8; RangeProof::size_in_bytes(1 << lg_nm[0], 1)];
+        r.read_exact(&mut buf)?;
+        RangeProof::from_bytes(&buf)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for RangeProof {
+    type Error = ProofError;
+
+    fn try_from(slice: &'a [u8]) -> Result<RangeProof, ProofError> {
+        RangeProof::from_bytes(slice)
+    }
+}
+
+impl From<RangeProof> for Vec<u8> {
+    fn from(proof: RangeProof) -> Vec<u8> {
+        proof.to_bytes()
+    }
+}
+
+/// Formats the proof as the lower-case hex encoding of
+/// [`RangeProof::to_bytes`].
+impl fmt::LowerHex for RangeProof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        util::write_hex(f, &self.to_bytes(), false)
+    }
+}
+
+/// Formats the proof as the upper-case hex encoding of
+/// [`RangeProof::to_bytes`].
+impl fmt::UpperHex for RangeProof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        util::write_hex(f, &self.to_bytes(), true)
+    }
+}
+
+/// Formats the proof in the same way as [`fmt::LowerHex`].
+impl fmt::Display for RangeProof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+/// Parses a proof from the hex encoding of its bytes, in either case.
+impl FromStr for RangeProof {
+    type Err = ProofError;
+
+    fn from_str(s: &str) -> Result<RangeProof, ProofError> {
+        RangeProof::from_bytes(&util::read_hex(s)?)
+    }
 }
 
 impl Serialize for RangeProof {
@@ -470,6 +1011,174 @@ impl<'de> Deserialize<'de> for RangeProof {
     }
 }
 
+/// Single-value range proofs as a [`ProofSystem`].
+///
+/// The witness is the value and its blinding factor, and the public
+/// input is the bitsize \\(n\\), the generators and the value
+/// commitment \\(V\\) the proof is for.  [`ProofSystem::prove`] returns
+/// [`ProofError::CommitmentMismatch`] if the witness does not open
+/// \\(V\\), and [`ProofSystem::verify`] checks the proof against
+/// \\(V\\), so a proof for any other commitment is rejected.
+///
+/// ```
+/// # extern crate curve25519_dalek;
+/// # use curve25519_dalek::scalar::Scalar;
+/// #
+/// # extern crate merlin;
+/// # use merlin::Transcript;
+/// #
+/// # extern crate bulletproofs;
+/// # use bulletproofs::{BulletproofGens, PedersenGens, ProofSystem, RangeProof};
+/// #
+/// # fn main() {
+/// let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(8, 1));
+/// let blinding = Scalar::from(1234u64);
+/// let commitment = pc_gens.commit(Scalar::from(7u64), blinding).compress();
+/// let other = pc_gens.commit(Scalar::from(8u64), blinding).compress();
+///
+/// let public = (8, pc_gens, bp_gens.clone(), commitment);
+/// let proof =
+///     RangeProof::prove((7, blinding), &public, &mut Transcript::new(b"doctest example")).unwrap();
+/// assert!(RangeProof::verify(&proof, &public, &mut Transcript::new(b"doctest example")).is_ok());
+///
+/// // The proof does not verify for another commitment.
+/// let public = (8, pc_gens, bp_gens, other);
+/// assert!(RangeProof::verify(&proof, &public, &mut Transcript::new(b"doctest example")).is_err());
+/// # }
+/// ```
+impl ProofSystem for RangeProof {
+    type Proof = RangeProof;
+    type Witness = (u64, Scalar);
+    type PublicInput = (usize, PedersenGens, BulletproofGens, CompressedRistretto);
+
+    fn prove(
+        witness: (u64, Scalar),
+        public: &(usize, PedersenGens, BulletproofGens, CompressedRistretto),
+        transcript: &mut Transcript,
+    ) -> Result<RangeProof, ProofError> {
+        let (v, v_blinding) = witness;
+        let (n, ref pc_gens, ref bp_gens, ref V) = *public;
+        RangeProof::prove_with_commitment(bp_gens, pc_gens, transcript, v, &v_blinding, V, n)
+    }
+
+    fn verify(
+        proof: &RangeProof,
+        public: &(usize, PedersenGens, BulletproofGens, CompressedRistretto),
+        transcript: &mut Transcript,
+    ) -> Result<(), ProofError> {
+        let (n, ref pc_gens, ref bp_gens, ref V) = *public;
+        proof.verify_single(bp_gens, pc_gens, transcript, V, n)
+    }
+}
+
+/// A [`RangeProof`] together with the value commitments it proves
+/// statements about, created by [`RangeProof::store_commitments`].
+#[derive(Clone, Debug)]
+pub struct RangeProofWithCommitments {
+    proof: RangeProof,
+    commitments: Vec<CompressedRistretto>,
+}
+
+impl RangeProofWithCommitments {
+    /// Returns the range proof.
+    pub fn proof(&self) -> &RangeProof {
+        &self.proof
+    }
+
+    /// Returns the value commitments.
+    pub fn commitments(&self) -> &[CompressedRistretto] {
+        &self.commitments
+    }
+
+    /// Splits this into the range proof and the value commitments.
+    pub fn into_parts(self) -> (RangeProof, Vec<CompressedRistretto>) {
+        (self.proof, self.commitments)
+    }
+
+    /// Verifies the range proof for the stored value commitments, see
+    /// [`RangeProof::verify_multiple`].
+    pub fn verify_multiple(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.proof
+            .verify_multiple(bp_gens, pc_gens, transcript, &self.commitments, n)
+    }
+}
+
+/// The challenges of the verification equation of a range proof,
+/// from which its scalars are computed on the fly.
+struct VerificationScalars {
+    m: usize,
+    x: Scalar,
+    y: Scalar,
+    z: Scalar,
+    c: Scalar,
+    a: Scalar,
+    b: Scalar,
+    basepoint_scalar: Scalar,
+    blinding_scalar: Scalar,
+    x_sq: Vec<Scalar>,
+    x_inv_sq: Vec<Scalar>,
+    s: Vec<Scalar>,
+    concat_z_and_2: Vec<Scalar>,
+}
+
+impl VerificationScalars {
+    /// Returns the scalars of the verification equation, for the
+    /// points returned by [`RangeProof::verification_points`].
+    fn scalars<'a>(&'a self) -> impl Iterator<Item = Scalar> + 'a {
+        let (x, z, c, a, b) = (self.x, self.z, self.c, self.a, self.b);
+        let zz = z * z;
+        let minus_z = -z;
+
+        let g = self.s.iter().map(move |s_i| minus_z - a * s_i);
+        let h = self
+            .s
+            .iter()
+            .rev()
+            .zip(util::exp_iter(self.y.invert()))
+            .zip(self.concat_z_and_2.iter())
+            .map(move |((s_i_inv, exp_y_inv), z_and_2)| {
+                z + exp_y_inv * (zz * z_and_2 - b * s_i_inv)
+            });
+
+        let value_commitment_scalars = util::exp_iter(z)
+            .take(self.m)
+            .map(move |z_exp| c * zz * z_exp);
+
+        iter::once(Scalar::one())
+            .chain(iter::once(x))
+            .chain(iter::once(c * x))
+            .chain(iter::once(c * x * x))
+            .chain(self.x_sq.iter().cloned())
+            .chain(self.x_inv_sq.iter().cloned())
+            .chain(iter::once(self.blinding_scalar))
+            .chain(iter::once(self.basepoint_scalar))
+            .chain(g)
+            .chain(h)
+            .chain(value_commitment_scalars)
+    }
+}
+
+/// Returns the smallest supported bitsize \\(n\\) such that
+/// \\(2^n \geq hi - lo\\), or `None` if the range \\([lo, hi)\\) is
+/// empty.
+fn range_bitsize(lo: u64, hi: u64) -> Option<usize> {
+    if lo >= hi {
+        return None;
+    }
+    let width = hi - lo;
+    [8, 16, 32]
+        .iter()
+        .cloned()
+        .find(|&n| width <= 1 << n)
+        .or(Some(64))
+}
+
 /// Compute
 /// \\[
 /// \delta(y,z) = (z - z^{2}) \langle \mathbf{1}, {\mathbf{y}}^{n \cdot m} \rangle - \sum_{j=0}^{m-1} z^{j+3} \cdot \langle \mathbf{1}, {\mathbf{2}}^{n \cdot m} \rangle
@@ -555,6 +1264,8 @@ mod tests {
             )
             .unwrap();
 
+            assert_eq!(proof.to_bytes().len(), RangeProof::size_in_bytes(n, m));
+
             // 2. Return serialized proof and value commitments
             (bincode::serialize(&proof).unwrap(), value_commitments)
         };
@@ -613,6 +1324,753 @@ mod tests {
         singleparty_create_and_verify_helper(64, 8);
     }
 
+    #[test]
+    fn size_in_bytes_matches_serialization() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 4);
+
+        for &n in &[8, 16, 32, 64] {
+            for &m in &[1, 2, 4] {
+                let values = vec![1u64; m];
+                let blindings = vec![Scalar::one(); m];
+                let mut transcript = Transcript::new(b"RangeProofTest");
+                let (proof, _) = RangeProof::prove_multiple(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &values,
+                    &blindings,
+                    n,
+                )
+                .unwrap();
+                assert_eq!(proof.to_bytes().len(), RangeProof::size_in_bytes(n, m));
+            }
+        }
+    }
+
+    fn prove_range_helper(v: u64, lo: u64, hi: u64) -> Result<(), ProofError> {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 2);
+        let v_blinding = Scalar::random(&mut rand::thread_rng());
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, V) =
+            RangeProof::prove_range(&bp_gens, &pc_gens, &mut transcript, v, &v_blinding, lo, hi)?;
+        assert_eq!(V, pc_gens.commit(Scalar::from(v), v_blinding).compress());
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        proof.verify_range(&bp_gens, &pc_gens, &mut transcript, &V, lo, hi)
+    }
+
+    #[test]
+    fn prove_range() {
+        assert!(prove_range_helper(0, 0, 256).is_ok());
+        assert!(prove_range_helper(255, 0, 256).is_ok());
+        assert!(prove_range_helper(256, 0, 256).is_err());
+
+        assert!(prove_range_helper(100, 100, 1000).is_ok());
+        assert!(prove_range_helper(999, 100, 1000).is_ok());
+        assert!(prove_range_helper(99, 100, 1000).is_err());
+        assert!(prove_range_helper(1000, 100, 1000).is_err());
+
+        assert!(prove_range_helper(41, 41, 42).is_ok());
+        assert!(prove_range_helper(42, 41, 42).is_err());
+
+        assert!(prove_range_helper(1 << 40, 0, u64::max_value()).is_ok());
+        assert_eq!(prove_range_helper(5, 5, 5), Err(ProofError::InvalidBitsize));
+    }
+
+    #[test]
+    fn verify_range_rejects_other_bounds() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 2);
+        let v_blinding = Scalar::random(&mut rand::thread_rng());
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, V) = RangeProof::prove_range(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            150,
+            &v_blinding,
+            100,
+            200,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert!(proof
+            .verify_range(&bp_gens, &pc_gens, &mut transcript, &V, 101, 201)
+            .is_err());
+    }
+
+    #[test]
+    fn prove_single_with_default_gens() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::default();
+        let blinding = Scalar::random(&mut rand::thread_rng());
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, V) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            u64::max_value(),
+            &blinding,
+            64,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert!(proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &V, 64)
+            .is_ok());
+    }
+
+    #[test]
+    fn prove_split() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 4);
+        let mut rng = rand::thread_rng();
+
+        let outputs = [10u64, 20, 30, 40];
+        let output_blindings: Vec<_> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+        let input_blinding: Scalar = output_blindings.iter().sum();
+        let input = pc_gens
+            .commit(Scalar::from(100u64), input_blinding)
+            .compress();
+
+        assert_eq!(
+            RangeProof::prove_split(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"RangeProofTest"),
+                99,
+                &input_blinding,
+                &outputs,
+                &output_blindings,
+                32,
+            )
+            .err(),
+            Some(ProofError::CommitmentMismatch)
+        );
+        assert_eq!(
+            RangeProof::prove_split(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"RangeProofTest"),
+                100,
+                &Scalar::one(),
+                &outputs,
+                &output_blindings,
+                32,
+            )
+            .err(),
+            Some(ProofError::CommitmentMismatch)
+        );
+
+        let (proof, commitments) = RangeProof::prove_split(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"RangeProofTest"),
+            100,
+            &input_blinding,
+            &outputs,
+            &output_blindings,
+            32,
+        )
+        .unwrap();
+
+        assert!(proof
+            .verify_split(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"RangeProofTest"),
+                &input,
+                &commitments,
+                32
+            )
+            .is_ok());
+
+        // The outputs do not balance a different input.
+        let other_input = pc_gens
+            .commit(Scalar::from(101u64), input_blinding)
+            .compress();
+        assert_eq!(
+            proof.verify_split(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"RangeProofTest"),
+                &other_input,
+                &commitments,
+                32
+            ),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn proofs_portable_between_gens_and_subset() {
+        let pc_gens = PedersenGens::default();
+        let full = BulletproofGens::new(256, 4);
+        let subset = full.subset(32, 2);
+        let values = [5u64, 6];
+        let blindings = [Scalar::one(), Scalar::one()];
+
+        for &(prover_gens, verifier_gens) in &[(&full, &subset), (&subset, &full)] {
+            let (proof, commitments) = RangeProof::prove_multiple(
+                prover_gens,
+                &pc_gens,
+                &mut Transcript::new(b"RangeProofTest"),
+                &values,
+                &blindings,
+                32,
+            )
+            .unwrap();
+            assert!(proof
+                .verify_multiple(
+                    verifier_gens,
+                    &pc_gens,
+                    &mut Transcript::new(b"RangeProofTest"),
+                    &commitments,
+                    32
+                )
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn verify_with_stored_commitments() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 2);
+        let blindings = [Scalar::one(), Scalar::from(2u64)];
+
+        let (proof, commitments) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"RangeProofTest"),
+            &[3, 4],
+            &blindings,
+            16,
+        )
+        .unwrap();
+        let stored = proof.store_commitments(commitments.clone());
+        assert_eq!(stored.commitments(), &commitments[..]);
+
+        assert!(stored
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"RangeProofTest"),
+                16
+            )
+            .is_ok());
+
+        let (proof, mut commitments) = stored.into_parts();
+        commitments.swap(0, 1);
+        assert!(proof
+            .store_commitments(commitments)
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"RangeProofTest"),
+                16
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn prove_single_rejects_oversized_bitsize() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(128, 1);
+        let mut transcript = Transcript::new(b"RangeProofTest");
+
+        assert_eq!(bp_gens.max_range_bits(), RANGE_PROOF_MAX_BITS);
+        assert_eq!(
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 1, &Scalar::one(), 128)
+                .err(),
+            Some(ProofError::InvalidBitsize)
+        );
+    }
+
+    #[test]
+    fn prove_with_commitment() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let blinding = Scalar::from(11u64);
+        let commitment = pc_gens.commit(Scalar::from(7u64), blinding).compress();
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let proof = RangeProof::prove_with_commitment(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            7,
+            &blinding,
+            &commitment,
+            32,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert!(proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &commitment, 32)
+            .is_ok());
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert_eq!(
+            RangeProof::prove_with_commitment(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                8,
+                &blinding,
+                &commitment,
+                32,
+            )
+            .err(),
+            Some(ProofError::CommitmentMismatch)
+        );
+    }
+
+    fn proof_system_roundtrip<P: ProofSystem>(
+        witness: P::Witness,
+        public: &P::PublicInput,
+    ) -> Result<(), ProofError> {
+        let proof = P::prove(witness, public, &mut Transcript::new(b"ProofSystemTest"))?;
+        P::verify(&proof, public, &mut Transcript::new(b"ProofSystemTest"))
+    }
+
+    #[test]
+    fn range_proof_as_proof_system() {
+        let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(8, 1));
+        let blinding = Scalar::random(&mut rand::thread_rng());
+        let public = |v: u64| {
+            let commitment = pc_gens.commit(Scalar::from(v), blinding).compress();
+            (8, pc_gens, bp_gens.clone(), commitment)
+        };
+
+        assert!(proof_system_roundtrip::<RangeProof>((255, blinding), &public(255)).is_ok());
+        assert_eq!(
+            proof_system_roundtrip::<RangeProof>((256, blinding), &public(256)),
+            Err(ProofError::VerificationError)
+        );
+        assert_eq!(
+            proof_system_roundtrip::<RangeProof>((7, blinding), &public(8)),
+            Err(ProofError::CommitmentMismatch)
+        );
+
+        let mut transcript = Transcript::new(b"ProofSystemTest");
+        let proof = RangeProof::prove((7, blinding), &public(7), &mut transcript).unwrap();
+        let mut transcript = Transcript::new(b"ProofSystemTest");
+        assert_eq!(
+            RangeProof::verify(&proof, &public(8), &mut transcript),
+            Err(ProofError::VerificationError)
+        );
+        let mut transcript = Transcript::new(b"OtherTranscript");
+        assert!(RangeProof::verify(&proof, &public(7), &mut transcript).is_err());
+    }
+
+    #[test]
+    fn commitment_binding() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let blinding = Scalar::random(&mut rand::thread_rng());
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, commitment) = RangeProof::prove_single_with_commitment_binding(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            7,
+            &blinding,
+            b"payment_1",
+            32,
+        )
+        .unwrap();
+
+        let verify = |label: &[u8], commitment: &CompressedRistretto| {
+            let mut transcript = Transcript::new(b"RangeProofTest");
+            proof.verify_single_with_commitment_binding(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                commitment,
+                label,
+                32,
+            )
+        };
+        assert!(verify(b"payment_1", &commitment).is_ok());
+        assert_eq!(
+            verify(b"payment_2", &commitment),
+            Err(ProofError::VerificationError)
+        );
+        let other_commitment = pc_gens.commit(Scalar::from(7u64), Scalar::one()).compress();
+        assert_eq!(
+            verify(b"payment_1", &other_commitment),
+            Err(ProofError::VerificationError)
+        );
+
+        // Without the binding, the labelled proof does not verify.
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert!(proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &commitment, 32)
+            .is_err());
+    }
+
+    #[test]
+    fn proof_bound_to_prior_transcript_messages() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let blinding = Scalar::from(3u64);
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        transcript.commit_point(b"prior", &pc_gens.B.compress());
+        let challenge = transcript.clone().challenge_scalar(b"c");
+        assert_ne!(
+            challenge,
+            Transcript::new(b"RangeProofTest").challenge_scalar(b"c")
+        );
+        let (proof, commitment) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 9, &blinding, 32)
+                .unwrap();
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        transcript.commit_point(b"prior", &pc_gens.B.compress());
+        assert!(proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &commitment, 32)
+            .is_ok());
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert!(proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &commitment, 32)
+            .is_err());
+    }
+
+    #[test]
+    fn verification_equation() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 2);
+
+        let equation = |v: u64, n: usize| {
+            let mut transcript = Transcript::new(b"RangeProofTest");
+            let (proof, commitment) = RangeProof::prove_single(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                v,
+                &Scalar::from(v + 1),
+                n,
+            )
+            .unwrap();
+
+            let mut transcript = Transcript::new(b"RangeProofTest");
+            let equation = proof
+                .verification_equation(&bp_gens, &pc_gens, &mut transcript, &[commitment], n)
+                .unwrap();
+            assert_eq!(equation.scalars().len(), equation.points().len());
+
+            let other = RangeProof::compressed_commitment_to_value(&pc_gens, v + 1, &Scalar::one());
+            let mut transcript = Transcript::new(b"RangeProofTest");
+            let wrong = proof
+                .verification_equation(&bp_gens, &pc_gens, &mut transcript, &[other], n)
+                .unwrap();
+            (equation, wrong)
+        };
+
+        let (eq_8, wrong_8) = equation(200, 8);
+        let (eq_16, wrong_16) = equation(60000, 16);
+        assert!(eq_8.check());
+        assert!(eq_16.check());
+        assert!(!wrong_8.check());
+        assert!(!wrong_16.check());
+
+        let mut rng = rand::thread_rng();
+        assert!(VerificationEquation::batch_check(
+            &[eq_8.clone(), eq_16.clone()],
+            &mut rng
+        ));
+        assert!(!VerificationEquation::batch_check(
+            &[eq_8, wrong_16, eq_16],
+            &mut rng
+        ));
+        assert!(VerificationEquation::batch_check(&[], &mut rng));
+    }
+
+    #[test]
+    fn prove_single_with_shared_blinding() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let mut rng = rand::thread_rng();
+        let blinding = Scalar::random(&mut rng);
+        let shares = ThresholdBlinding::split(blinding, 2, 3, &mut rng);
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, commitment) = RangeProof::prove_single_with_shared_blinding(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            7,
+            &shares[1..],
+            2,
+            32,
+        )
+        .unwrap();
+        assert_eq!(
+            commitment,
+            RangeProof::compressed_commitment_to_value(&pc_gens, 7, &blinding)
+        );
+        assert_eq!(
+            RangeProof::commitment_to_value(&pc_gens, 7, &blinding),
+            Scalar::from(7u64) * pc_gens.B + blinding * pc_gens.B_blinding
+        );
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert!(proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &commitment, 32)
+            .is_ok());
+
+        // A single share does not determine the blinding factor.
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert_eq!(
+            RangeProof::prove_single_with_shared_blinding(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                7,
+                &shares[2..],
+                2,
+                32,
+            )
+            .map(|_| ()),
+            Err(ProofError::InvalidBlindingShares)
+        );
+    }
+
+    #[cfg(feature = "timing")]
+    #[test]
+    fn prove_single_timed() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, V, timings) = RangeProof::prove_single_timed(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            7,
+            &Scalar::one(),
+            64,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert!(proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &V, 64)
+            .is_ok());
+        assert!(timings.commitment_us > 0);
+        assert!(timings.inner_product_us > 0);
+        assert_eq!(timings.serialized_len, RangeProof::size_in_bytes(64, 1));
+    }
+
+    #[test]
+    fn hex_roundtrip() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, _) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 7, &Scalar::one(), 8)
+                .unwrap();
+
+        let hex = format!("{:x}", proof);
+        assert_eq!(hex.len(), 2 * RangeProof::size_in_bytes(8, 1));
+        assert_eq!(format!("{}", proof), hex);
+        assert_eq!(format!("{:X}", proof), hex.to_uppercase());
+
+        let parsed: RangeProof = hex.parse().unwrap();
+        assert_eq!(parsed.to_bytes(), proof.to_bytes());
+        let parsed: RangeProof = hex.to_uppercase().parse().unwrap();
+        assert_eq!(parsed.to_bytes(), proof.to_bytes());
+
+        assert_eq!(
+            hex[1..].parse::<RangeProof>().err(),
+            Some(ProofError::FormatError)
+        );
+    }
+
+    #[test]
+    fn io_roundtrip() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let blinding = Scalar::from(5u64);
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, commitment) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 12, &blinding, 32)
+                .unwrap();
+
+        let mut cursor = io::Cursor::new(Vec::new());
+        proof.write_to(&mut cursor).unwrap();
+        proof.write_to(&mut cursor).unwrap();
+        assert_eq!(cursor.get_ref()[0], 5);
+        assert_eq!(
+            &cursor.get_ref()[1..1 + 9 * 32 + 10 * 32],
+            &proof.to_bytes()[..]
+        );
+
+        // Each read consumes one proof.
+        cursor.set_position(0);
+        let read = RangeProof::read_from(&mut cursor).unwrap();
+        assert_eq!(read.to_bytes(), proof.to_bytes());
+        let second = RangeProof::read_from(&mut cursor).unwrap();
+        assert_eq!(second.to_bytes(), proof.to_bytes());
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        assert!(read
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &commitment, 32)
+            .is_ok());
+
+        struct FailingReader;
+        impl io::Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+            }
+        }
+        match RangeProof::read_from(&mut FailingReader) {
+            Err(ProofError::IoError(e)) => {
+                assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+                assert_eq!(e.get_ref().to_string(), "closed");
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        match RangeProof::read_from(&mut io::Cursor::new(vec![0u8; 31])) {
+            Err(ProofError::IoError(e)) => assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof),
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(
+            RangeProof::read_from(&mut io::Cursor::new(vec![32u8; 1024])).err(),
+            Some(ProofError::FormatError)
+        );
+    }
+
+    #[test]
+    fn try_from_bytes_roundtrip() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, _) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 7, &Scalar::one(), 32)
+                .unwrap();
+
+        let bytes = proof.to_bytes();
+        let parsed = RangeProof::try_from(&bytes[..]).unwrap();
+        assert_eq!(Vec::<u8>::from(parsed), bytes);
+
+        assert_eq!(
+            RangeProof::try_from(&bytes[1..]).err(),
+            Some(ProofError::FormatError)
+        );
+    }
+
+    #[test]
+    fn verification_reports_invalid_point() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, V) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 7, &Scalar::one(), 8)
+                .unwrap();
+        let bytes = proof.to_bytes();
+
+        // An all-0xff encoding is not a valid Ristretto point.  It is
+        // parsed and serialized as is, and rejected by verification.
+        let corrupt = |offset: usize| {
+            let mut bytes = bytes.clone();
+            for b in bytes[offset..offset + 32].iter_mut() {
+                *b = 0xff;
+            }
+            let proof = RangeProof::from_bytes(&bytes).unwrap();
+            assert_eq!(proof.to_bytes(), bytes);
+            assert!(proof.try_to_bytes().is_err());
+            let mut transcript = Transcript::new(b"RangeProofTest");
+            let result = proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &V, 8);
+            let mut transcript = Transcript::new(b"RangeProofTest");
+            assert_eq!(
+                proof
+                    .verification_equation(&bp_gens, &pc_gens, &mut transcript, &[V], 8)
+                    .err(),
+                result.clone().err()
+            );
+            result.err()
+        };
+
+        assert_eq!(
+            corrupt(32),
+            Some(ProofError::InvalidPoint {
+                byte_offset: 32,
+                field: "S",
+                index: None,
+            })
+        );
+        // The inner product proof follows the 7 elements of the
+        // range proof, as pairs L_0, R_0, L_1, R_1, ...
+        assert_eq!(
+            corrupt(7 * 32),
+            Some(ProofError::InvalidPoint {
+                byte_offset: 7 * 32,
+                field: "L",
+                index: Some(0),
+            })
+        );
+        assert_eq!(
+            corrupt(10 * 32),
+            Some(ProofError::InvalidPoint {
+                byte_offset: 10 * 32,
+                field: "R",
+                index: Some(1),
+            })
+        );
+        assert_eq!(
+            corrupt(10 * 32).unwrap().to_string(),
+            "Proof data has an invalid point R[1] at byte offset 320."
+        );
+        assert_eq!(proof.try_to_bytes(), Ok(bytes.clone()));
+    }
+
+    #[test]
+    fn from_bytes_rejects_noncanonical_scalars() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
+        let mut transcript = Transcript::new(b"RangeProofTest");
+        let (proof, _) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 7, &Scalar::one(), 8)
+                .unwrap();
+        let bytes = proof.to_bytes();
+
+        // The little-endian encoding of the group order l, the
+        // smallest non-canonical scalar encoding.
+        let l: [u8; 32] = [
+            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9,
+            0xde, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x10,
+        ];
+        let corrupt = |offset: usize| {
+            let mut bytes = bytes.clone();
+            bytes[offset..offset + 32].copy_from_slice(&l);
+            RangeProof::from_bytes(&bytes).err()
+        };
+
+        // t_x, t_x_blinding and e_blinding follow the four points.
+        for i in 4..7 {
+            assert_eq!(corrupt(i * 32), Some(ProofError::FormatError));
+        }
+        // The scalars a, b follow the 3 pairs L_i, R_i of the inner
+        // product proof.
+        for i in 13..15 {
+            assert_eq!(corrupt(i * 32), Some(ProofError::FormatError));
+        }
+    }
+
     #[test]
     fn detect_dishonest_party_during_aggregation() {
         use self::dealer::*;
@@ -0,0 +1,368 @@
+//! The `scalar_point_inner_product_proof` module contains API for
+//! proving knowledge of a vector of scalars whose inner product with a
+//! public vector of points is a given point.
+
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+use std::iter;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, IsIdentity, MultiscalarMul, VartimeMultiscalarMul};
+use merlin::Transcript;
+
+use errors::ProofError;
+use transcript::TranscriptProtocol;
+use util;
+
+/// A proof that the prover knows scalars \\(\mathbf{a}\\) with
+/// \\(\langle \mathbf{a}, \mathbf{G} \rangle = P\\) for a public vector
+/// of points \\(\mathbf{G}\\) and a public point \\(P\\).
+///
+/// The argument folds \\(\mathbf{a}\\) and \\(\mathbf{G}\\) in half in
+/// each round, as the [`InnerProductProof`](::inner_product_proof::InnerProductProof)
+/// does for its \\(\mathbf{a}\\) and \\(\mathbf{G}\\) vectors, so the
+/// proof consists of \\(2 \lg n\\) points and a single scalar.
+/// Vectors whose length is not a power of two are implicitly padded
+/// with zero scalars and identity points.
+///
+/// The points \\(\mathbf{G}\\) are committed to the transcript along
+/// with \\(P\\), so the proof is bound to the vector it was made for.
+///
+/// Like the inner product proof, this argument is **not**
+/// zero-knowledge: the final scalar reveals a combination of the
+/// entries of \\(\mathbf{a}\\).
+#[derive(Clone, Debug)]
+pub struct ScalarPointInnerProductProof {
+    L_vec: Vec<CompressedRistretto>,
+    R_vec: Vec<CompressedRistretto>,
+    a: Scalar,
+}
+
+impl ScalarPointInnerProductProof {
+    /// Creates a proof of knowledge of `a` for the point
+    /// \\(P = \langle \mathbf{a}, \mathbf{G} \rangle\\).
+    ///
+    /// Returns a tuple `(proof, P)` of the proof and the point.
+    ///
+    /// Returns an error if `a` and `G` have different lengths.
+    pub fn prove(
+        transcript: &mut Transcript,
+        a: &[Scalar],
+        G: &[RistrettoPoint],
+    ) -> Result<(ScalarPointInnerProductProof, CompressedRistretto), ProofError> {
+        if a.len() != G.len() {
+            return Err(ProofError::FormatError);
+        }
+        let mut n = a.len().next_power_of_two();
+
+        let mut a_vec: Vec<Scalar> = a
+            .iter()
+            .cloned()
+            .chain(iter::repeat(Scalar::zero()))
+            .take(n)
+            .collect();
+        let mut G_vec: Vec<RistrettoPoint> = G
+            .iter()
+            .cloned()
+            .chain(iter::repeat(RistrettoPoint::identity()))
+            .take(n)
+            .collect();
+
+        let P = RistrettoPoint::multiscalar_mul(a_vec.iter(), G_vec.iter()).compress();
+
+        transcript.scalar_point_innerproduct_domain_sep(n as u64);
+        transcript.commit_point(b"P", &P);
+        for G_i in G {
+            transcript.commit_point(b"G", &G_i.compress());
+        }
+
+        let mut a = &mut a_vec[..];
+        let mut G = &mut G_vec[..];
+
+        let lg_n = n.trailing_zeros() as usize;
+        let mut L_vec = Vec::with_capacity(lg_n);
+        let mut R_vec = Vec::with_capacity(lg_n);
+
+        while n != 1 {
+            n /= 2;
+            let (a_L, a_R) = a.split_at_mut(n);
+            let (G_L, G_R) = G.split_at_mut(n);
+
+            let L = RistrettoPoint::multiscalar_mul(a_L.iter(), G_R.iter()).compress();
+            let R = RistrettoPoint::multiscalar_mul(a_R.iter(), G_L.iter()).compress();
+
+            L_vec.push(L);
+            R_vec.push(R);
+
+            transcript.commit_point(b"L", &L);
+            transcript.commit_point(b"R", &R);
+
+            let u = transcript.challenge_scalar(b"u");
+            let u_inv = u.invert();
+
+            for i in 0..n {
+                a_L[i] = a_L[i] * u + u_inv * a_R[i];
+                G_L[i] = RistrettoPoint::vartime_multiscalar_mul(&[u_inv, u], &[G_L[i], G_R[i]]);
+            }
+
+            a = a_L;
+            G = G_L;
+        }
+
+        Ok((
+            ScalarPointInnerProductProof {
+                L_vec,
+                R_vec,
+                a: a[0],
+            },
+            P,
+        ))
+    }
+
+    /// Verifies that the prover knows scalars whose inner product
+    /// with `G` is `P`.
+    pub fn verify(
+        &self,
+        transcript: &mut Transcript,
+        G: &[RistrettoPoint],
+        P: &CompressedRistretto,
+    ) -> Result<(), ProofError> {
+        let n = G.len().next_power_of_two();
+        let lg_n = self.L_vec.len();
+        if lg_n >= 32 || n != (1 << lg_n) {
+            return Err(ProofError::VerificationError);
+        }
+
+        transcript.scalar_point_innerproduct_domain_sep(n as u64);
+        transcript.commit_point(b"P", P);
+        for G_i in G {
+            transcript.commit_point(b"G", &G_i.compress());
+        }
+
+        let mut challenges = Vec::with_capacity(lg_n);
+        for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
+            transcript.commit_point(b"L", L);
+            transcript.commit_point(b"R", R);
+            challenges.push(transcript.challenge_scalar(b"u"));
+        }
+
+        let mut challenges_inv = challenges.clone();
+        let allinv = Scalar::batch_invert(&mut challenges_inv);
+
+        let u_sq: Vec<Scalar> = challenges.iter().map(|u| u * u).collect();
+        let u_inv_sq: Vec<Scalar> = challenges_inv.iter().map(|u| u * u).collect();
+
+        // The folded generator is <s, G>, with s computed as in the
+        // inner product proof.
+        let mut s = Vec::with_capacity(n);
+        s.push(allinv);
+        for i in 1..n {
+            let lg_i = (32 - 1 - (i as u32).leading_zeros()) as usize;
+            let k = 1 << lg_i;
+            s.push(s[i - k] * u_sq[(lg_n - 1) - lg_i]);
+        }
+
+        // a * <s, G> = P + sum_j (u_j^2 * L_j + u_j^-2 * R_j)
+        let check = RistrettoPoint::optional_multiscalar_mul(
+            s.iter()
+                .take(G.len())
+                .map(|s_i| self.a * s_i)
+                .chain(u_sq.iter().map(|u| -u))
+                .chain(u_inv_sq.iter().map(|u| -u))
+                .chain(iter::once(-Scalar::one())),
+            G.iter()
+                .map(|G_i| Some(*G_i))
+                .chain(self.L_vec.iter().map(|L| L.decompress()))
+                .chain(self.R_vec.iter().map(|R| R.decompress()))
+                .chain(iter::once(P.decompress())),
+        )
+        .ok_or_else(|| util::invalid_point_error(self.encoded_points()))?;
+
+        if check.is_identity() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Returns the points \\(L\_j, R\_j\\) with their offsets in the
+    /// encoding, field names and indices, for
+    /// [`util::invalid_point_error`].
+    fn encoded_points(&self) -> impl Iterator<Item = util::EncodedPoint<'_>> {
+        self.L_vec
+            .iter()
+            .zip(self.R_vec.iter())
+            .enumerate()
+            .flat_map(|(i, (L, R))| {
+                iter::once((2 * i * 32, "L", Some(i), L)).chain(iter::once((
+                    (2 * i + 1) * 32,
+                    "R",
+                    Some(i),
+                    R,
+                )))
+            })
+    }
+
+    /// Serializes the proof into a byte array of \\(2 \lg n + 1\\)
+    /// 32-byte elements, where \\(n\\) is the padded vector length.
+    ///
+    /// The layout of the encoding is:
+    ///
+    /// * \\(\lg n\\) pairs of compressed Ristretto points \\(L_0,R_0\dots,L_{\lg n-1},R_{\lg n-1}\\),
+    /// * the scalar \\(a\\).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity((2 * self.L_vec.len() + 1) * 32);
+        for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
+            buf.extend_from_slice(L.as_bytes());
+            buf.extend_from_slice(R.as_bytes());
+        }
+        buf.extend_from_slice(self.a.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a
+    /// `ScalarPointInnerProductProof`.  Invalid points are reported by
+    /// [`ScalarPointInnerProductProof::verify`].
+    pub fn from_bytes(slice: &[u8]) -> Result<ScalarPointInnerProductProof, ProofError> {
+        if slice.len() % 32 != 0 {
+            return Err(ProofError::FormatError);
+        }
+        let num_elements = slice.len() / 32;
+        if num_elements % 2 != 1 {
+            return Err(ProofError::FormatError);
+        }
+        let lg_n = (num_elements - 1) / 2;
+        if lg_n >= 32 {
+            return Err(ProofError::FormatError);
+        }
+
+        use util::{read32, read_point};
+
+        let mut L_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
+        let mut R_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
+        for i in 0..lg_n {
+            let pos = 2 * i * 32;
+            L_vec.push(read_point(slice, pos));
+            R_vec.push(read_point(slice, pos + 32));
+        }
+
+        let a = Scalar::from_canonical_bytes(read32(&slice[2 * lg_n * 32..]))
+            .ok_or(ProofError::FormatError)?;
+
+        Ok(ScalarPointInnerProductProof { L_vec, R_vec, a })
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for ScalarPointInnerProductProof {
+    type Error = ProofError;
+
+    fn try_from(slice: &'a [u8]) -> Result<ScalarPointInnerProductProof, ProofError> {
+        ScalarPointInnerProductProof::from_bytes(slice)
+    }
+}
+
+impl From<ScalarPointInnerProductProof> for Vec<u8> {
+    fn from(proof: ScalarPointInnerProductProof) -> Vec<u8> {
+        proof.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::thread_rng;
+
+    use generators::BulletproofGens;
+
+    fn prove_and_verify_helper(n: usize) {
+        let bp_gens = BulletproofGens::new(n.next_power_of_two(), 1);
+        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+        let mut rng = thread_rng();
+
+        let a: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+
+        let mut transcript = Transcript::new(b"ScalarPointIPPTest");
+        let (proof, P) = ScalarPointInnerProductProof::prove(&mut transcript, &a, &G).unwrap();
+        assert_eq!(P, RistrettoPoint::multiscalar_mul(&a, &G).compress());
+
+        let proof = ScalarPointInnerProductProof::from_bytes(&proof.to_bytes()).unwrap();
+
+        let mut transcript = Transcript::new(b"ScalarPointIPPTest");
+        assert!(proof.verify(&mut transcript, &G, &P).is_ok());
+
+        let mut transcript = Transcript::new(b"ScalarPointIPPTest");
+        let wrong_P = (P.decompress().unwrap() + G[0]).compress();
+        assert_eq!(
+            proof.verify(&mut transcript, &G, &wrong_P),
+            Err(ProofError::VerificationError)
+        );
+
+        let mut transcript = Transcript::new(b"ScalarPointIPPTest");
+        let H: Vec<RistrettoPoint> = bp_gens.share(0).H(n).cloned().collect();
+        assert_eq!(
+            proof.verify(&mut transcript, &H, &P),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn prove_and_verify_1() {
+        prove_and_verify_helper(1);
+    }
+
+    #[test]
+    fn prove_and_verify_3() {
+        prove_and_verify_helper(3);
+    }
+
+    #[test]
+    fn prove_and_verify_16() {
+        prove_and_verify_helper(16);
+    }
+
+    #[test]
+    fn verify_rejects_changed_generators() {
+        let bp_gens = BulletproofGens::new(4, 1);
+        let mut G: Vec<RistrettoPoint> = bp_gens.share(0).G(4).cloned().collect();
+        let mut rng = thread_rng();
+
+        // With a zero last scalar, P does not depend on the last point.
+        let a = vec![
+            Scalar::random(&mut rng),
+            Scalar::random(&mut rng),
+            Scalar::random(&mut rng),
+            Scalar::zero(),
+        ];
+
+        let mut transcript = Transcript::new(b"ScalarPointIPPTest");
+        let (proof, P) = ScalarPointInnerProductProof::prove(&mut transcript, &a, &G).unwrap();
+
+        G[3] = bp_gens.share(0).H(1).cloned().next().unwrap();
+        assert_eq!(P, RistrettoPoint::multiscalar_mul(&a, &G).compress());
+
+        let mut transcript = Transcript::new(b"ScalarPointIPPTest");
+        assert_eq!(
+            proof.verify(&mut transcript, &G, &P),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn prove_rejects_mismatched_lengths() {
+        let mut transcript = Transcript::new(b"ScalarPointIPPTest");
+        assert_eq!(
+            ScalarPointInnerProductProof::prove(
+                &mut transcript,
+                &[Scalar::one(); 2],
+                &[RistrettoPoint::identity(); 3],
+            )
+            .err(),
+            Some(ProofError::FormatError)
+        );
+    }
+}
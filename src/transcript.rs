@@ -5,6 +5,43 @@ use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 
+/// Extension trait for the Merlin [`Transcript`], with the methods
+/// used by the proofs in this crate to commit their messages and draw
+/// challenges.
+///
+/// Callers can use it to commit their own protocol messages to a
+/// transcript before passing it to a prover or verifier, so that the
+/// proof's challenges depend on them.  The verifier must commit the
+/// same messages in the same order.
+///
+/// # Labels
+///
+/// A range proof commits, in order:
+///
+/// * `rangeproof_domain_sep(n, m)`;
+/// * each value commitment as `"V"`;
+/// * the points `"A"` and `"S"`, then draws the challenges `"y"` and `"z"`;
+/// * the points `"T_1"` and `"T_2"`, then draws the challenge `"x"`;
+/// * the scalars `"t_x"`, `"t_x_blinding"` and `"e_blinding"`, then
+///   draws the challenge `"w"`;
+/// * its inner product proof.
+///
+/// A range proof created with
+/// [`RangeProof::prove_single_with_commitment_binding`](::RangeProof::prove_single_with_commitment_binding)
+/// is preceded by `commitment_binding(label, V)`.
+///
+/// An inner product proof commits `innerproduct_domain_sep(n)`, then
+/// for each folding round the points `"L"` and `"R"`, drawing the
+/// challenge `"u"` after each pair.  A scalar-point inner product
+/// proof does the same after `scalar_point_innerproduct_domain_sep(n)`
+/// and the point `"P"`.
+///
+/// An R1CS proof commits `r1cs_domain_sep()`, each high-level
+/// variable's commitment as `"V"` and the number of multipliers as
+/// `"m"`, then follows the range proof's steps with the points
+/// `"A_I"`, `"A_O"` and `"S"` in place of `"A"` and `"S"`, and
+/// `"T_1"`, `"T_3"`, `"T_4"`, `"T_5"` and `"T_6"` in place of
+/// `"T_1"` and `"T_2"`.
 pub trait TranscriptProtocol {
     /// Commit a domain separator for an `n`-bit, `m`-party range proof.
     fn rangeproof_domain_sep(&mut self, n: u64, m: u64);
@@ -12,6 +49,15 @@ pub trait TranscriptProtocol {
     fn innerproduct_domain_sep(&mut self, n: u64);
     /// Commit a domain separator for a constraint system.
     fn r1cs_domain_sep(&mut self);
+    /// Commit a domain separator for a proof of opening of a length-`n` vector commitment.
+    fn vector_opening_domain_sep(&mut self, n: u64);
+    /// Commit a domain separator for a length-`n` proof of inner product of committed vectors.
+    fn vector_innerproduct_domain_sep(&mut self, n: u64);
+    /// Commit a domain separator for a length-`n` proof of inner product of scalars and points.
+    fn scalar_point_innerproduct_domain_sep(&mut self, n: u64);
+    /// Commit a caller-supplied `label` and the value commitment \\(V\\)
+    /// it binds a proof to.
+    fn commitment_binding(&mut self, label: &[u8], commitment: &CompressedRistretto);
     /// Commit a 64-bit integer.
     fn commit_u64(&mut self, label: &'static [u8], n: u64);
     /// Commit a `scalar` with the given `label`.
@@ -44,6 +90,27 @@ impl TranscriptProtocol for Transcript {
         self.commit_bytes(b"dom-sep", b"r1cs v1");
     }
 
+    fn vector_opening_domain_sep(&mut self, n: u64) {
+        self.commit_bytes(b"dom-sep", b"vector-opening v1");
+        self.commit_bytes(b"n", &le_u64(n));
+    }
+
+    fn vector_innerproduct_domain_sep(&mut self, n: u64) {
+        self.commit_bytes(b"dom-sep", b"vector-ipp v1");
+        self.commit_bytes(b"n", &le_u64(n));
+    }
+
+    fn scalar_point_innerproduct_domain_sep(&mut self, n: u64) {
+        self.commit_bytes(b"dom-sep", b"scalar-point-ipp v1");
+        self.commit_bytes(b"n", &le_u64(n));
+    }
+
+    fn commitment_binding(&mut self, label: &[u8], commitment: &CompressedRistretto) {
+        self.commit_bytes(b"dom-sep", b"commitment-binding v1");
+        self.commit_bytes(b"label", label);
+        self.commit_point(b"V", commitment);
+    }
+
     fn commit_u64(&mut self, label: &'static [u8], n: u64) {
         self.commit_bytes(label, &le_u64(n));
     }
@@ -2,8 +2,11 @@
 #![allow(non_snake_case)]
 
 use clear_on_drop::clear::Clear;
+use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
+use errors::ProofError;
 use inner_product_proof::inner_product;
+use std::fmt;
 
 /// Represents a degree-1 vector polynomial \\(\mathbf{a} + \mathbf{b} \cdot x\\).
 pub struct VecPoly1(pub Vec<Scalar>, pub Vec<Scalar>);
@@ -228,6 +231,94 @@ pub fn scalar_exp_vartime(x: &Scalar, mut n: u64) -> Scalar {
     result
 }
 
+/// Raises `x` to the power of the 256-bit little-endian integer
+/// `exp` using binary exponentiation.
+fn scalar_pow_vartime(x: &Scalar, exp: &[u8; 32]) -> Scalar {
+    let mut result = Scalar::one();
+    for byte in exp.iter().rev() {
+        for i in (0..8).rev() {
+            result *= result;
+            if (byte >> i) & 1 == 1 {
+                result *= x;
+            }
+        }
+    }
+    result
+}
+
+/// Shifts the 256-bit little-endian integer `x` right by one bit.
+fn shr1(x: &[u8; 32]) -> [u8; 32] {
+    let mut result = [0u8; 32];
+    for (i, byte) in result.iter_mut().enumerate() {
+        *byte = (x[i] >> 1) | x.get(i + 1).map_or(0, |next| next << 7);
+    }
+    result
+}
+
+/// Returns whether `s` is a square modulo the group order \\(\ell\\),
+/// by computing the Legendre symbol \\(s^{(\ell-1)/2}\\).
+///
+/// Zero is considered a square.  This function is not constant-time.
+pub fn is_quadratic_residue(s: &Scalar) -> bool {
+    let half_order = shr1(&(-Scalar::one()).to_bytes());
+    scalar_pow_vartime(s, &half_order) != -Scalar::one()
+}
+
+/// Computes a square root of `s` modulo the group order \\(\ell\\)
+/// using the Tonelli-Shanks algorithm, or returns `None` if `s` is
+/// not a square.
+///
+/// Of the two square roots, the smaller one, at most
+/// \\((\ell-1)/2\\), is returned.  This function is not constant-time.
+pub fn scalar_sqrt(s: &Scalar) -> Option<Scalar> {
+    if !is_quadratic_residue(s) {
+        return None;
+    }
+    if *s == Scalar::zero() {
+        return Some(Scalar::zero());
+    }
+
+    // Write l - 1 = 2^e * q with q odd; for the Ristretto group
+    // order e = 2.
+    let mut q = (-Scalar::one()).to_bytes();
+    let mut e = 0;
+    while q[0] & 1 == 0 {
+        q = shr1(&q);
+        e += 1;
+    }
+    // 2 is a non-residue modulo l.
+    let z = Scalar::from(2u64);
+
+    let mut m = e;
+    let mut c = scalar_pow_vartime(&z, &q);
+    let mut t = scalar_pow_vartime(s, &q);
+    let mut r = scalar_pow_vartime(s, &shr1(&(Scalar::from_bits(q) + Scalar::one()).to_bytes()));
+    while t != Scalar::one() {
+        // Find the least i such that t^(2^i) = 1.
+        let mut i = 0;
+        let mut t2i = t;
+        while t2i != Scalar::one() {
+            t2i *= t2i;
+            i += 1;
+        }
+        let mut b = c;
+        for _ in 0..(m - i - 1) {
+            b *= b;
+        }
+        m = i;
+        c = b * b;
+        t *= c;
+        r *= b;
+    }
+
+    // Compare the little-endian encodings as integers.
+    let half_order = shr1(&(-Scalar::one()).to_bytes());
+    if r.as_bytes().iter().rev().gt(half_order.iter().rev()) {
+        r = -r;
+    }
+    Some(r)
+}
+
 /// Takes the sum of all the powers of `x`, up to `n`
 /// If `n` is a power of 2, it uses the efficient algorithm with `2*lg n` multiplications and additions.
 /// If `n` is not a power of 2, it uses the slow algorithm with `n` multiplications and additions.
@@ -262,6 +353,81 @@ pub fn read32(data: &[u8]) -> [u8; 32] {
     buf32
 }
 
+/// Given `data` with `len >= offset + 32`, read the compressed point
+/// at `offset`.
+///
+/// The point is not decompressed: verification decompresses it and
+/// reports its offset with [`invalid_point_error`] if that fails.
+pub fn read_point(data: &[u8], offset: usize) -> CompressedRistretto {
+    CompressedRistretto(read32(&data[offset..]))
+}
+
+/// A point of a proof with its offset in the proof encoding, its
+/// field name, and its index if the field is a vector of points.
+pub type EncodedPoint<'a> = (usize, &'static str, Option<usize>, &'a CompressedRistretto);
+
+/// Returns the [`ProofError::InvalidPoint`] for the first of `points`
+/// that does not decompress, or `None` if they all decompress.
+pub fn find_invalid_point<'a, I>(points: I) -> Option<ProofError>
+where
+    I: IntoIterator<Item = EncodedPoint<'a>>,
+{
+    points
+        .into_iter()
+        .find(|(_, _, _, point)| point.decompress().is_none())
+        .map(|(byte_offset, field, index, _)| ProofError::InvalidPoint {
+            byte_offset,
+            field,
+            index,
+        })
+}
+
+/// Returns the [`ProofError::InvalidPoint`] for the first of `points`
+/// that does not decompress, or [`ProofError::VerificationError`] if
+/// they all decompress.
+///
+/// Verifiers call this only after a decompression failed, so that the
+/// points of a valid proof are decompressed once.
+pub fn invalid_point_error<'a, I>(points: I) -> ProofError
+where
+    I: IntoIterator<Item = EncodedPoint<'a>>,
+{
+    find_invalid_point(points).unwrap_or(ProofError::VerificationError)
+}
+
+/// Writes `bytes` to `f` as a hex string, with upper-case digits if
+/// `upper` is set.
+pub fn write_hex(f: &mut fmt::Formatter, bytes: &[u8], upper: bool) -> fmt::Result {
+    for b in bytes {
+        if upper {
+            write!(f, "{:02X}", b)?;
+        } else {
+            write!(f, "{:02x}", b)?;
+        }
+    }
+    Ok(())
+}
+
+/// Parses a hex string with upper- or lower-case digits.
+///
+/// Returns [`ProofError::FormatError`] if `s` has odd length or
+/// contains a non-hex character.
+pub fn read_hex(s: &str) -> Result<Vec<u8>, ProofError> {
+    if s.len() % 2 != 0 {
+        return Err(ProofError::FormatError);
+    }
+    let digit = |c: u8| {
+        (c as char)
+            .to_digit(16)
+            .map(|d| d as u8)
+            .ok_or(ProofError::FormatError)
+    };
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| Ok((digit(pair[0])? << 4) | digit(pair[1])?))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -293,6 +459,41 @@ mod tests {
         assert_eq!(Scalar::from(40u64), inner_product(&a, &b));
     }
 
+    #[test]
+    fn test_read_hex() {
+        assert_eq!(read_hex("00ff7fA0").unwrap(), vec![0x00, 0xff, 0x7f, 0xa0]);
+        assert_eq!(read_hex("").unwrap(), Vec::<u8>::new());
+        assert_eq!(read_hex("abc"), Err(ProofError::FormatError));
+        assert_eq!(read_hex("0g"), Err(ProofError::FormatError));
+        assert_eq!(read_hex("é0"), Err(ProofError::FormatError));
+    }
+
+    #[test]
+    fn test_scalar_sqrt() {
+        use rand;
+
+        assert_eq!(scalar_sqrt(&Scalar::from(4u64)), Some(Scalar::from(2u64)));
+        assert_eq!(scalar_sqrt(&Scalar::zero()), Some(Scalar::zero()));
+        assert_eq!(scalar_sqrt(&Scalar::one()), Some(Scalar::one()));
+
+        // 2 is not a square modulo l, but 3 is.
+        assert!(!is_quadratic_residue(&Scalar::from(2u64)));
+        assert_eq!(scalar_sqrt(&Scalar::from(2u64)), None);
+        assert!(is_quadratic_residue(&Scalar::from(3u64)));
+        let r = scalar_sqrt(&Scalar::from(3u64)).unwrap();
+        assert_eq!(r * r, Scalar::from(3u64));
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..16 {
+            let x = Scalar::random(&mut rng);
+            let r = scalar_sqrt(&(x * x)).unwrap();
+            assert!(r == x || r == -x);
+            assert!(r.as_bytes().iter().rev().le((-r).as_bytes().iter().rev()));
+            assert!(is_quadratic_residue(&(x * x)));
+            assert!(!is_quadratic_residue(&(Scalar::from(2u64) * x * x)));
+        }
+    }
+
     /// Raises `x` to the power `n`.
     fn scalar_exp_vartime_slow(x: &Scalar, n: u64) -> Scalar {
         let mut result = Scalar::one();
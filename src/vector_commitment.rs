@@ -0,0 +1,282 @@
+//! The `vector_commitment` module contains API for committing to a
+//! vector of scalars and proving knowledge of the commitment's opening.
+
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+use std::iter;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, MultiscalarMul, VartimeMultiscalarMul};
+use merlin::Transcript;
+
+use errors::ProofError;
+use generators::{BulletproofGens, PedersenGens};
+use transcript::TranscriptProtocol;
+
+/// A Pedersen commitment to a vector of scalars,
+/// \\(C = \langle \mathbf{v}, \mathbf{G} \rangle + \widetilde{v} \cdot \widetilde{B}\\).
+///
+/// The \\(\mathbf G\\) generators are the first party's generators
+/// of a [`BulletproofGens`], and the blinding base \\(\widetilde{B}\\)
+/// is the `B_blinding` generator of a [`PedersenGens`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PedersenVectorCommitment(pub CompressedRistretto);
+
+impl PedersenVectorCommitment {
+    /// Commits to `values` using the given `blinding` factor.
+    ///
+    /// Returns an error if `bp_gens` has fewer than `values.len()`
+    /// generators per party.
+    pub fn commit(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        values: &[Scalar],
+        blinding: Scalar,
+    ) -> Result<PedersenVectorCommitment, ProofError> {
+        let n = values.len();
+        if bp_gens.gens_capacity < n || bp_gens.party_capacity < 1 {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
+        let C = RistrettoPoint::multiscalar_mul(
+            iter::once(&blinding).chain(values.iter()),
+            iter::once(&pc_gens.B_blinding).chain(bp_gens.share(0).G(n)),
+        );
+
+        Ok(PedersenVectorCommitment(C.compress()))
+    }
+}
+
+/// A Schnorr-style proof that the prover knows the blinding factor
+/// opening a [`PedersenVectorCommitment`] to a given vector of values.
+///
+/// The values are known to the verifier; only the blinding factor
+/// remains secret.  Since no range bounds are involved, the proof
+/// consists of a single point and a single scalar.
+#[derive(Clone, Debug)]
+pub struct OpeningProof {
+    /// Commitment to the prover's nonce
+    R: CompressedRistretto,
+    /// Response to the challenge
+    s: Scalar,
+}
+
+impl OpeningProof {
+    /// Creates a proof that `commitment` opens to `values` with the
+    /// given `blinding` factor.
+    ///
+    /// Returns [`ProofError::CommitmentMismatch`] if `commitment` is
+    /// not a commitment to `values` with `blinding`, so that a proof is
+    /// never produced for the wrong commitment.
+    pub fn prove(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        commitment: &PedersenVectorCommitment,
+        values: &[Scalar],
+        blinding: &Scalar,
+    ) -> Result<OpeningProof, ProofError> {
+        if PedersenVectorCommitment::commit(bp_gens, pc_gens, values, *blinding)? != *commitment {
+            return Err(ProofError::CommitmentMismatch);
+        }
+
+        transcript.vector_opening_domain_sep(values.len() as u64);
+        transcript.commit_point(b"C", &commitment.0);
+        for v in values {
+            transcript.commit_scalar(b"v", v);
+        }
+
+        let mut rng = {
+            use rand::thread_rng;
+            transcript
+                .build_rng()
+                .commit_witness_bytes(b"blinding", blinding.as_bytes())
+                .finalize(&mut thread_rng())
+        };
+
+        let k = Scalar::random(&mut rng);
+        let R = (k * pc_gens.B_blinding).compress();
+        transcript.commit_point(b"R", &R);
+
+        let c = transcript.challenge_scalar(b"c");
+        let s = k + c * blinding;
+
+        Ok(OpeningProof { R, s })
+    }
+
+    /// Verifies that the prover knows the blinding factor opening
+    /// `commitment` to `values`.
+    pub fn verify(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        commitment: &PedersenVectorCommitment,
+        values: &[Scalar],
+    ) -> Result<(), ProofError> {
+        let n = values.len();
+        if bp_gens.gens_capacity < n || bp_gens.party_capacity < 1 {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
+        transcript.vector_opening_domain_sep(n as u64);
+        transcript.commit_point(b"C", &commitment.0);
+        for v in values {
+            transcript.commit_scalar(b"v", v);
+        }
+        transcript.commit_point(b"R", &self.R);
+
+        let c = transcript.challenge_scalar(b"c");
+
+        // Check that s * B_blinding = R + c * (C - <v, G>)
+        let check = RistrettoPoint::optional_multiscalar_mul(
+            iter::once(Scalar::one())
+                .chain(iter::once(c))
+                .chain(iter::once(-self.s))
+                .chain(values.iter().map(|v| -c * v)),
+            iter::once(self.R.decompress())
+                .chain(iter::once(commitment.0.decompress()))
+                .chain(iter::once(Some(pc_gens.B_blinding)))
+                .chain(bp_gens.share(0).G(n).map(|&G_i| Some(G_i))),
+        )
+        .ok_or(ProofError::VerificationError)?;
+
+        if check.is_identity() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Serializes the proof into a byte array of 64 bytes: the
+    /// compressed point \\(R\\) followed by the scalar \\(s\\).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(64);
+        buf.extend_from_slice(self.R.as_bytes());
+        buf.extend_from_slice(self.s.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the slice is not 64 bytes long, or if the
+    /// scalar is not canonical.
+    pub fn from_bytes(slice: &[u8]) -> Result<OpeningProof, ProofError> {
+        if slice.len() != 64 {
+            return Err(ProofError::FormatError);
+        }
+
+        use util::read32;
+
+        let R = CompressedRistretto(read32(&slice[0..]));
+        let s =
+            Scalar::from_canonical_bytes(read32(&slice[32..])).ok_or(ProofError::FormatError)?;
+
+        Ok(OpeningProof { R, s })
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for OpeningProof {
+    type Error = ProofError;
+
+    fn try_from(slice: &'a [u8]) -> Result<OpeningProof, ProofError> {
+        OpeningProof::from_bytes(slice)
+    }
+}
+
+impl From<OpeningProof> for Vec<u8> {
+    fn from(proof: OpeningProof) -> Vec<u8> {
+        proof.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_values(n: usize) -> Vec<Scalar> {
+        let mut rng = rand::thread_rng();
+        (0..n).map(|_| Scalar::random(&mut rng)).collect()
+    }
+
+    #[test]
+    fn opening_proof_roundtrip() {
+        let bp_gens = BulletproofGens::new(16, 1);
+        let pc_gens = PedersenGens::default();
+
+        let values = random_values(16);
+        let blinding = Scalar::random(&mut rand::thread_rng());
+        let commitment =
+            PedersenVectorCommitment::commit(&bp_gens, &pc_gens, &values, blinding).unwrap();
+
+        let mut transcript = Transcript::new(b"OpeningProofTest");
+        let proof = OpeningProof::prove(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &commitment,
+            &values,
+            &blinding,
+        )
+        .unwrap();
+
+        let proof = OpeningProof::from_bytes(&proof.to_bytes()).unwrap();
+
+        let mut transcript = Transcript::new(b"OpeningProofTest");
+        assert!(proof
+            .verify(&bp_gens, &pc_gens, &mut transcript, &commitment, &values)
+            .is_ok());
+
+        // The proof does not verify for different values
+        let mut other_values = values.clone();
+        other_values[3] += Scalar::one();
+        let mut transcript = Transcript::new(b"OpeningProofTest");
+        assert!(proof
+            .verify(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &commitment,
+                &other_values
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn opening_proof_rejects_wrong_blinding() {
+        let bp_gens = BulletproofGens::new(4, 1);
+        let pc_gens = PedersenGens::default();
+
+        let values = random_values(4);
+        let blinding = Scalar::random(&mut rand::thread_rng());
+        let commitment =
+            PedersenVectorCommitment::commit(&bp_gens, &pc_gens, &values, blinding).unwrap();
+
+        let mut transcript = Transcript::new(b"OpeningProofTest");
+        assert_eq!(
+            OpeningProof::prove(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &commitment,
+                &values,
+                &(blinding + Scalar::one()),
+            )
+            .err(),
+            Some(ProofError::CommitmentMismatch)
+        );
+    }
+
+    #[test]
+    fn commit_checks_generators_length() {
+        let bp_gens = BulletproofGens::new(4, 1);
+        let pc_gens = PedersenGens::default();
+
+        assert_eq!(
+            PedersenVectorCommitment::commit(&bp_gens, &pc_gens, &random_values(8), Scalar::one()),
+            Err(ProofError::InvalidGeneratorsLength)
+        );
+    }
+}
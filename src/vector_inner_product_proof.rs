@@ -0,0 +1,485 @@
+//! The `vector_inner_product_proof` module contains API for proving
+//! that two separately committed vectors have a given inner product.
+
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+use std::iter;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, MultiscalarMul, VartimeMultiscalarMul};
+use merlin::Transcript;
+
+use errors::ProofError;
+use generators::{BulletproofGens, PedersenGens};
+use inner_product_proof::{inner_product, InnerProductProof};
+use transcript::TranscriptProtocol;
+use util;
+
+/// A proof that two committed vectors \\(\mathbf{a}\\), \\(\mathbf{b}\\)
+/// have inner product \\(\langle \mathbf{a}, \mathbf{b} \rangle = c\\)
+/// for a public value \\(c\\).
+///
+/// The vectors are committed separately, as
+/// \\(A = \langle \mathbf{a}, \mathbf{G} \rangle + \widetilde{a} \cdot \widetilde{B}\\) and
+/// \\(B = \langle \mathbf{b}, \mathbf{H} \rangle + \widetilde{b} \cdot \widetilde{B}\\),
+/// where \\(\mathbf G\\), \\(\mathbf H\\) are the first party's
+/// generators of a [`BulletproofGens`] and \\(\widetilde{B}\\) is the
+/// `B_blinding` generator of a [`PedersenGens`].
+///
+/// Vectors whose length is not a power of two are implicitly padded
+/// with zeros, so the commitments are unaffected by the padding.
+///
+/// After \\(A\\) and \\(B\\) are committed, a challenge \\(y\\) binds
+/// them into \\(A + y \cdot B\\), with \\(\mathbf H\\) rescaled by
+/// \\(y\\) in the inner-product argument, so that neither commitment
+/// can absorb a point taken from the other.
+///
+/// The protocol otherwise follows the range proof: the prover blinds the
+/// vectors as \\(\mathbf{l}(x) = \mathbf{a} + \mathbf{s}\_L x\\),
+/// \\(\mathbf{r}(x) = \mathbf{b} + \mathbf{s}\_R x\\), commits to the
+/// coefficients of \\(t(x) = \langle \mathbf{l}(x), \mathbf{r}(x) \rangle\\),
+/// whose constant term is \\(c\\), and finishes with an
+/// [`InnerProductProof`] for \\(\mathbf{l}(x)\\), \\(\mathbf{r}(x)\\).
+#[derive(Clone, Debug)]
+pub struct VectorInnerProductProof {
+    /// Commitment to the blinding factors
+    S: CompressedRistretto,
+    /// Commitment to the \\(t_1\\) coefficient of \\( t(x) \\)
+    T_1: CompressedRistretto,
+    /// Commitment to the \\(t_2\\) coefficient of \\( t(x) \\)
+    T_2: CompressedRistretto,
+    /// Evaluation of the polynomial \\(t(x)\\) at the challenge point \\(x\\)
+    t_x: Scalar,
+    /// Blinding factor for the synthetic commitment to \\(t(x)\\)
+    t_x_blinding: Scalar,
+    /// Blinding factor for the synthetic commitment to the inner-product arguments
+    e_blinding: Scalar,
+    /// Proof data for the inner-product argument.
+    ipp_proof: InnerProductProof,
+}
+
+impl VectorInnerProductProof {
+    /// Commits to `a` and `b` and creates a proof that their inner
+    /// product is \\(\langle \mathbf{a}, \mathbf{b} \rangle\\).
+    ///
+    /// Returns a tuple `(proof, A, B)` of the proof and the
+    /// commitments to `a` and `b` with blinding factors `a_blinding`
+    /// and `b_blinding`.
+    ///
+    /// Returns an error if `a` and `b` have different lengths, or if
+    /// `bp_gens` has too few generators per party for them.
+    pub fn prove(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        a: &[Scalar],
+        a_blinding: &Scalar,
+        b: &[Scalar],
+        b_blinding: &Scalar,
+    ) -> Result<
+        (
+            VectorInnerProductProof,
+            CompressedRistretto,
+            CompressedRistretto,
+        ),
+        ProofError,
+    > {
+        if a.len() != b.len() {
+            return Err(ProofError::FormatError);
+        }
+        let n = a.len().next_power_of_two();
+        if bp_gens.gens_capacity < n || bp_gens.party_capacity < 1 {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
+        let zero = Scalar::zero();
+        let a: Vec<Scalar> = a
+            .iter()
+            .cloned()
+            .chain(iter::repeat(zero))
+            .take(n)
+            .collect();
+        let b: Vec<Scalar> = b
+            .iter()
+            .cloned()
+            .chain(iter::repeat(zero))
+            .take(n)
+            .collect();
+
+        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+        let H: Vec<RistrettoPoint> = bp_gens.share(0).H(n).cloned().collect();
+
+        let A = RistrettoPoint::multiscalar_mul(
+            iter::once(a_blinding).chain(a.iter()),
+            iter::once(&pc_gens.B_blinding).chain(G.iter()),
+        )
+        .compress();
+        let B = RistrettoPoint::multiscalar_mul(
+            iter::once(b_blinding).chain(b.iter()),
+            iter::once(&pc_gens.B_blinding).chain(H.iter()),
+        )
+        .compress();
+        let c = inner_product(&a, &b);
+
+        transcript.vector_innerproduct_domain_sep(n as u64);
+        transcript.commit_point(b"A", &A);
+        transcript.commit_point(b"B", &B);
+        transcript.commit_scalar(b"c", &c);
+
+        let y = transcript.challenge_scalar(b"y");
+
+        let mut rng = {
+            use rand::thread_rng;
+            let mut builder = transcript
+                .build_rng()
+                .commit_witness_bytes(b"a_blinding", a_blinding.as_bytes())
+                .commit_witness_bytes(b"b_blinding", b_blinding.as_bytes());
+            for (a_i, b_i) in a.iter().zip(b.iter()) {
+                builder = builder
+                    .commit_witness_bytes(b"a_i", a_i.as_bytes())
+                    .commit_witness_bytes(b"b_i", b_i.as_bytes());
+            }
+            builder.finalize(&mut thread_rng())
+        };
+
+        // Commit to the blinding vectors s_L, s_R, with s_R against y * H
+        let s_blinding = Scalar::random(&mut rng);
+        let s_L: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let s_R: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let y_s_R: Vec<Scalar> = s_R.iter().map(|s_i| s_i * y).collect();
+        let S = RistrettoPoint::multiscalar_mul(
+            iter::once(&s_blinding)
+                .chain(s_L.iter())
+                .chain(y_s_R.iter()),
+            iter::once(&pc_gens.B_blinding)
+                .chain(G.iter())
+                .chain(H.iter()),
+        )
+        .compress();
+        transcript.commit_point(b"S", &S);
+
+        // Commit to the coefficients of t(x) = c + t_1 x + t_2 x^2
+        let t_1 = inner_product(&a, &s_R) + inner_product(&s_L, &b);
+        let t_2 = inner_product(&s_L, &s_R);
+        let t_1_blinding = Scalar::random(&mut rng);
+        let t_2_blinding = Scalar::random(&mut rng);
+        let T_1 = pc_gens.commit(t_1, t_1_blinding).compress();
+        let T_2 = pc_gens.commit(t_2, t_2_blinding).compress();
+        transcript.commit_point(b"T_1", &T_1);
+        transcript.commit_point(b"T_2", &T_2);
+
+        let x = transcript.challenge_scalar(b"x");
+
+        let l_vec: Vec<Scalar> = a
+            .iter()
+            .zip(s_L.iter())
+            .map(|(a_i, s_i)| a_i + s_i * x)
+            .collect();
+        let r_vec: Vec<Scalar> = b
+            .iter()
+            .zip(s_R.iter())
+            .map(|(b_i, s_i)| b_i + s_i * x)
+            .collect();
+
+        let t_x = inner_product(&l_vec, &r_vec);
+        let t_x_blinding = t_1_blinding * x + t_2_blinding * x * x;
+        let e_blinding = a_blinding + y * b_blinding + s_blinding * x;
+
+        transcript.commit_scalar(b"t_x", &t_x);
+        transcript.commit_scalar(b"t_x_blinding", &t_x_blinding);
+        transcript.commit_scalar(b"e_blinding", &e_blinding);
+
+        // Get a challenge value to combine statements for the IPP
+        let w = transcript.challenge_scalar(b"w");
+        let Q = w * pc_gens.B;
+
+        let ipp_proof = InnerProductProof::create(transcript, &Q, &vec![y; n], G, H, l_vec, r_vec);
+
+        Ok((
+            VectorInnerProductProof {
+                S,
+                T_1,
+                T_2,
+                t_x,
+                t_x_blinding,
+                e_blinding,
+                ipp_proof,
+            },
+            A,
+            B,
+        ))
+    }
+
+    /// Verifies that the vectors committed to by `A` and `B`, of
+    /// length `n`, have inner product `c`.
+    pub fn verify(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        A: &CompressedRistretto,
+        B: &CompressedRistretto,
+        c: &Scalar,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        let n = n.next_power_of_two();
+        if bp_gens.gens_capacity < n || bp_gens.party_capacity < 1 {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
+        transcript.vector_innerproduct_domain_sep(n as u64);
+        transcript.commit_point(b"A", A);
+        transcript.commit_point(b"B", B);
+        transcript.commit_scalar(b"c", c);
+
+        let y = transcript.challenge_scalar(b"y");
+
+        transcript.commit_point(b"S", &self.S);
+        transcript.commit_point(b"T_1", &self.T_1);
+        transcript.commit_point(b"T_2", &self.T_2);
+
+        let x = transcript.challenge_scalar(b"x");
+
+        transcript.commit_scalar(b"t_x", &self.t_x);
+        transcript.commit_scalar(b"t_x_blinding", &self.t_x_blinding);
+        transcript.commit_scalar(b"e_blinding", &self.e_blinding);
+
+        let w = transcript.challenge_scalar(b"w");
+        let Q = w * pc_gens.B;
+
+        // Check that t(x) is consistent with c, T_1, T_2:
+        // t_x * B + t_x_blinding * B_blinding = c * B + x * T_1 + x^2 * T_2
+        let check = RistrettoPoint::optional_multiscalar_mul(
+            vec![self.t_x - c, self.t_x_blinding, -x, -x * x],
+            vec![
+                Some(pc_gens.B),
+                Some(pc_gens.B_blinding),
+                self.T_1.decompress(),
+                self.T_2.decompress(),
+            ],
+        )
+        .ok_or_else(|| util::invalid_point_error(self.encoded_points()))?;
+        if !check.is_identity() {
+            return Err(ProofError::VerificationError);
+        }
+
+        // P = A + y * B + x * S - e_blinding * B_blinding + t_x * Q
+        //   = <l(x), G> + <r(x), y * H> + <l(x), r(x)> * Q
+        let P = RistrettoPoint::optional_multiscalar_mul(
+            vec![Scalar::one(), y, x, -self.e_blinding, self.t_x],
+            vec![
+                A.decompress(),
+                B.decompress(),
+                self.S.decompress(),
+                Some(pc_gens.B_blinding),
+                Some(Q),
+            ],
+        )
+        .ok_or_else(|| util::invalid_point_error(self.encoded_points()))?;
+
+        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+        let H: Vec<RistrettoPoint> = bp_gens.share(0).H(n).cloned().collect();
+
+        self.ipp_proof
+            .verify(n, transcript, iter::repeat(y).take(n), &P, &Q, &G, &H)
+    }
+
+    /// Returns the points of the proof with their offsets in the
+    /// encoding, field names and indices, for
+    /// [`util::invalid_point_error`].
+    fn encoded_points(&self) -> impl Iterator<Item = util::EncodedPoint<'_>> {
+        vec![
+            (0, "S", None, &self.S),
+            (32, "T_1", None, &self.T_1),
+            (2 * 32, "T_2", None, &self.T_2),
+        ]
+        .into_iter()
+        .chain(
+            self.ipp_proof
+                .encoded_points()
+                .map(|(offset, field, index, point)| (6 * 32 + offset, field, index, point)),
+        )
+    }
+
+    /// Serializes the proof into a byte array of \\(3 + 3 + 2 \lg n + 2\\)
+    /// 32-byte elements, where \\(n\\) is the padded vector length.
+    ///
+    /// The layout of the encoding is:
+    ///
+    /// * three compressed Ristretto points \\(S,T_1,T_2\\),
+    /// * three scalars \\(t_x, \tilde{t}_x, \tilde{e}\\),
+    /// * the encoding of the [`InnerProductProof`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(6 * 32 + self.ipp_proof.serialized_size());
+        buf.extend_from_slice(self.S.as_bytes());
+        buf.extend_from_slice(self.T_1.as_bytes());
+        buf.extend_from_slice(self.T_2.as_bytes());
+        buf.extend_from_slice(self.t_x.as_bytes());
+        buf.extend_from_slice(self.t_x_blinding.as_bytes());
+        buf.extend_from_slice(self.e_blinding.as_bytes());
+        buf.extend_from_slice(self.ipp_proof.to_bytes().as_slice());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a
+    /// `VectorInnerProductProof`.  Invalid points are reported by
+    /// [`VectorInnerProductProof::verify`].
+    pub fn from_bytes(slice: &[u8]) -> Result<VectorInnerProductProof, ProofError> {
+        if slice.len() % 32 != 0 || slice.len() < 6 * 32 {
+            return Err(ProofError::FormatError);
+        }
+
+        use util::{read32, read_point};
+
+        let S = read_point(slice, 0);
+        let T_1 = read_point(slice, 1 * 32);
+        let T_2 = read_point(slice, 2 * 32);
+
+        let t_x = Scalar::from_canonical_bytes(read32(&slice[3 * 32..]))
+            .ok_or(ProofError::FormatError)?;
+        let t_x_blinding = Scalar::from_canonical_bytes(read32(&slice[4 * 32..]))
+            .ok_or(ProofError::FormatError)?;
+        let e_blinding = Scalar::from_canonical_bytes(read32(&slice[5 * 32..]))
+            .ok_or(ProofError::FormatError)?;
+
+        let ipp_proof = InnerProductProof::from_bytes(&slice[6 * 32..])?;
+
+        Ok(VectorInnerProductProof {
+            S,
+            T_1,
+            T_2,
+            t_x,
+            t_x_blinding,
+            e_blinding,
+            ipp_proof,
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for VectorInnerProductProof {
+    type Error = ProofError;
+
+    fn try_from(slice: &'a [u8]) -> Result<VectorInnerProductProof, ProofError> {
+        VectorInnerProductProof::from_bytes(slice)
+    }
+}
+
+impl From<VectorInnerProductProof> for Vec<u8> {
+    fn from(proof: VectorInnerProductProof) -> Vec<u8> {
+        proof.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::thread_rng;
+
+    fn prove_and_verify_helper(n: usize) {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n.next_power_of_two(), 1);
+        let mut rng = thread_rng();
+
+        let a: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let c = inner_product(&a, &b);
+        let (a_blinding, b_blinding) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+
+        let mut transcript = Transcript::new(b"VectorIPPTest");
+        let (proof, A, B) = VectorInnerProductProof::prove(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &a,
+            &a_blinding,
+            &b,
+            &b_blinding,
+        )
+        .unwrap();
+
+        let proof = VectorInnerProductProof::from_bytes(&proof.to_bytes()).unwrap();
+
+        let mut transcript = Transcript::new(b"VectorIPPTest");
+        assert!(proof
+            .verify(&bp_gens, &pc_gens, &mut transcript, &A, &B, &c, n)
+            .is_ok());
+
+        let mut transcript = Transcript::new(b"VectorIPPTest");
+        assert_eq!(
+            proof.verify(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &A,
+                &B,
+                &(c + Scalar::one()),
+                n
+            ),
+            Err(ProofError::VerificationError)
+        );
+
+        let mut transcript = Transcript::new(b"VectorIPPTest");
+        assert_eq!(
+            proof.verify(&bp_gens, &pc_gens, &mut transcript, &B, &A, &c, n),
+            Err(ProofError::VerificationError)
+        );
+
+        // Shifting a point from B to A keeps A + B unchanged.
+        let shift = pc_gens.B_blinding;
+        let A_shifted = (A.decompress().unwrap() + shift).compress();
+        let B_shifted = (B.decompress().unwrap() - shift).compress();
+        let mut transcript = Transcript::new(b"VectorIPPTest");
+        assert_eq!(
+            proof.verify(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &A_shifted,
+                &B_shifted,
+                &c,
+                n
+            ),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn prove_and_verify_1() {
+        prove_and_verify_helper(1);
+    }
+
+    #[test]
+    fn prove_and_verify_3() {
+        prove_and_verify_helper(3);
+    }
+
+    #[test]
+    fn prove_and_verify_16() {
+        prove_and_verify_helper(16);
+    }
+
+    #[test]
+    fn prove_rejects_mismatched_lengths() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(4, 1);
+        let mut transcript = Transcript::new(b"VectorIPPTest");
+        assert_eq!(
+            VectorInnerProductProof::prove(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &[Scalar::one(); 2],
+                &Scalar::one(),
+                &[Scalar::one(); 3],
+                &Scalar::one(),
+            )
+            .err(),
+            Some(ProofError::FormatError)
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! The `verification_equation` module contains API for inspecting
+//! and batching the final check of a proof's verifier.
+
+#![allow(non_snake_case)]
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+use rand::{CryptoRng, RngCore};
+
+/// The final check of a verifier, stating that
+/// \\(\sum_i c_i \cdot P_i = 0\\) for scalars \\(c_i\\) and points
+/// \\(P_i\\).
+///
+/// All challenges have been recomputed from the transcript when the
+/// equation is created, e.g. by [`RangeProof::verification_equation`](::RangeProof::verification_equation),
+/// so the equation can be inspected or handed to an external
+/// verifier as-is.
+#[derive(Clone, Debug)]
+pub struct VerificationEquation {
+    scalars: Vec<Scalar>,
+    points: Vec<RistrettoPoint>,
+}
+
+impl VerificationEquation {
+    /// Creates the equation \\(\sum_i c_i \cdot P_i = 0\\) for the
+    /// given `scalars` \\(c_i\\) and `points` \\(P_i\\).
+    ///
+    /// Panics if `scalars` and `points` have different lengths.
+    pub fn new(scalars: Vec<Scalar>, points: Vec<RistrettoPoint>) -> Self {
+        assert_eq!(
+            scalars.len(),
+            points.len(),
+            "VerificationEquation::new: lengths of vectors do not match"
+        );
+        VerificationEquation { scalars, points }
+    }
+
+    /// Returns the scalars \\(c_i\\) of the equation.
+    pub fn scalars(&self) -> &[Scalar] {
+        &self.scalars
+    }
+
+    /// Returns the points \\(P_i\\) of the equation.
+    pub fn points(&self) -> &[RistrettoPoint] {
+        &self.points
+    }
+
+    /// Evaluates the equation using a single multiscalar
+    /// multiplication, returning whether it holds.
+    pub fn check(&self) -> bool {
+        RistrettoPoint::vartime_multiscalar_mul(&self.scalars, &self.points).is_identity()
+    }
+
+    /// Checks several equations at once, by evaluating their sum with
+    /// random weights drawn from `rng` in a single multiscalar
+    /// multiplication.
+    ///
+    /// Returns `true` if all equations hold, and `false` except with
+    /// negligible probability if any of them does not.
+    pub fn batch_check<R: RngCore + CryptoRng>(
+        equations: &[VerificationEquation],
+        rng: &mut R,
+    ) -> bool {
+        let weights: Vec<Scalar> = equations.iter().map(|_| Scalar::random(rng)).collect();
+        // The multiscalar multiplication needs exact lengths, which
+        // flattened iterators do not report.
+        let scalars: Vec<Scalar> = equations
+            .iter()
+            .zip(weights.iter())
+            .flat_map(|(eq, weight)| eq.scalars.iter().map(move |c| weight * c))
+            .collect();
+        let points: Vec<RistrettoPoint> = equations
+            .iter()
+            .flat_map(|eq| eq.points.iter().cloned())
+            .collect();
+        RistrettoPoint::vartime_multiscalar_mul(&scalars, &points).is_identity()
+    }
+}
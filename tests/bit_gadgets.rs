@@ -0,0 +1,131 @@
+#![allow(non_snake_case)]
+extern crate bulletproofs;
+extern crate curve25519_dalek;
+extern crate merlin;
+extern crate rand;
+
+use bulletproofs::r1cs::gadgets::modular::mul_mod_2n;
+use bulletproofs::r1cs::gadgets::*;
+use bulletproofs::r1cs::*;
+use bulletproofs::{BulletproofGens, PedersenGens};
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand::thread_rng;
+
+#[derive(Copy, Clone, Debug)]
+enum BitOp {
+    Xor,
+    And,
+    Or,
+    Not,
+}
+
+impl BitOp {
+    fn eval(self, a: u8, b: u8) -> u8 {
+        match self {
+            BitOp::Xor => a ^ b,
+            BitOp::And => a & b,
+            BitOp::Or => a | b,
+            BitOp::Not => !a,
+        }
+    }
+}
+
+/// Constrains `c = op(a, b)` for 8-bit integers.
+fn bit_op_gadget<CS: ConstraintSystem>(
+    cs: &mut CS,
+    op: BitOp,
+    vars: &[Variable],
+    values: Option<(u8, u8)>,
+) -> Result<(), R1CSError> {
+    let a_bits = bit_decompose(cs, vars[0], values.map(|(a, _)| a.into()), 8)?;
+    let b_bits = bit_decompose(cs, vars[1], values.map(|(_, b)| b.into()), 8)?;
+    let c_bits = match op {
+        BitOp::Xor => xor_bits(cs, &a_bits, &b_bits),
+        BitOp::And => and_bits(cs, &a_bits, &b_bits),
+        BitOp::Or => or_bits(cs, &a_bits, &b_bits),
+        BitOp::Not => not_bits(&a_bits),
+    };
+    cs.constrain(pack_bits(c_bits) - vars[2]);
+    Ok(())
+}
+
+fn bit_op_roundtrip_helper(op: BitOp, a: u8, b: u8, c: u8) -> Result<(), R1CSError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+
+    let (proof, commitments) = {
+        let mut transcript = Transcript::new(b"R1CSBitGadgets");
+        let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+        let values: Vec<_> = [a, b, c].iter().map(|&x| Scalar::from(x)).collect();
+        let blindings: Vec<_> = (0..3).map(|_| Scalar::random(&mut thread_rng())).collect();
+        let (commitments, vars) = prover.commit_batch(&values, &blindings)?;
+
+        let mut cs = prover.finalize_inputs();
+        bit_op_gadget(&mut cs, op, &vars, Some((a, b)))?;
+        (cs.prove()?, commitments)
+    };
+
+    let mut transcript = Transcript::new(b"R1CSBitGadgets");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let vars = verifier.commit_batch(&commitments);
+    let mut cs = verifier.finalize_inputs();
+    bit_op_gadget(&mut cs, op, &vars, None)?;
+    cs.verify(&proof)
+}
+
+fn mul_mod_2n_roundtrip_helper(n: usize, a: u64, b: u64, c: u64) -> Result<(), R1CSError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(512, 1);
+
+    let (proof, commitments) = {
+        let mut transcript = Transcript::new(b"R1CSMulMod2n");
+        let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+        let values: Vec<_> = [a, b, c].iter().map(|&x| Scalar::from(x)).collect();
+        let blindings: Vec<_> = (0..3).map(|_| Scalar::random(&mut thread_rng())).collect();
+        let (commitments, vars) = prover.commit_batch(&values, &blindings)?;
+
+        let mut cs = prover.finalize_inputs();
+        bit_decompose(&mut cs, vars[0], Some(a), n)?;
+        bit_decompose(&mut cs, vars[1], Some(b), n)?;
+        let r = mul_mod_2n(&mut cs, vars[0], vars[1], Some((a, b)), n)?;
+        cs.constrain(r - vars[2]);
+        (cs.prove()?, commitments)
+    };
+
+    let mut transcript = Transcript::new(b"R1CSMulMod2n");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let vars = verifier.commit_batch(&commitments);
+    let mut cs = verifier.finalize_inputs();
+    bit_decompose(&mut cs, vars[0], None, n)?;
+    bit_decompose(&mut cs, vars[1], None, n)?;
+    let r = mul_mod_2n(&mut cs, vars[0], vars[1], None, n)?;
+    cs.constrain(r - vars[2]);
+    cs.verify(&proof)
+}
+
+#[test]
+fn mul_mod_2n_wraps() {
+    assert!(mul_mod_2n_roundtrip_helper(8, 255, 255, 1).is_ok());
+    assert!(mul_mod_2n_roundtrip_helper(8, 255, 255, 255 * 255).is_err());
+    assert!(mul_mod_2n_roundtrip_helper(8, 255, 255, 2).is_err());
+
+    let (a, b) = (std::u64::MAX - 6, 0x1234_5678_9abc_def0);
+    assert!(mul_mod_2n_roundtrip_helper(64, a, b, a.wrapping_mul(b)).is_ok());
+    assert!(mul_mod_2n_roundtrip_helper(64, a, b, a.wrapping_mul(b).wrapping_add(1)).is_err());
+}
+
+#[test]
+fn bit_ops() {
+    let (a, b) = (0b1011_0010, 0b0110_1100);
+    for &op in &[BitOp::Xor, BitOp::And, BitOp::Or, BitOp::Not] {
+        let c = op.eval(a, b);
+        assert!(bit_op_roundtrip_helper(op, a, b, c).is_ok(), "{:?}", op);
+        assert!(
+            bit_op_roundtrip_helper(op, a, b, c ^ 1).is_err(),
+            "{:?}",
+            op
+        );
+    }
+    assert_eq!(BitOp::Xor.eval(a, b), 0b1101_1110);
+}
@@ -0,0 +1,246 @@
+#![allow(non_snake_case)]
+extern crate bulletproofs;
+extern crate curve25519_dalek;
+extern crate merlin;
+extern crate quickcheck;
+extern crate rand;
+
+use bulletproofs::r1cs::*;
+use bulletproofs::{BulletproofGens, PedersenGens};
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+use rand::{thread_rng, Rng};
+
+// Random arithmetic circuits
+
+/// A gate reading two earlier wires and producing a new one.
+#[derive(Copy, Clone, Debug)]
+enum Gate {
+    Add(usize, usize),
+    Sub(usize, usize),
+    Mul(usize, usize),
+}
+
+/// A random DAG of gates over `num_inputs` input wires.
+///
+/// Wires `0..num_inputs` are the inputs, and gate `i` produces wire
+/// `num_inputs + i`.  The last wire is the output of the circuit.
+#[derive(Clone, Debug)]
+struct Circuit {
+    num_inputs: usize,
+    gates: Vec<Gate>,
+}
+
+/// The assignments `(l, r, o)` for each multiplication gate.
+type Witness = Vec<(Scalar, Scalar, Scalar)>;
+
+impl Circuit {
+    fn random<R: Rng>(rng: &mut R, num_inputs: usize, num_gates: usize) -> Circuit {
+        let gates = (0..num_gates)
+            .map(|i| {
+                let num_wires = num_inputs + i;
+                let a = rng.gen_range(0, num_wires);
+                let b = rng.gen_range(0, num_wires);
+                // Make sure there is at least one multiplier to corrupt.
+                match if i == 0 { 2 } else { rng.gen_range(0, 3) } {
+                    0 => Gate::Add(a, b),
+                    1 => Gate::Sub(a, b),
+                    _ => Gate::Mul(a, b),
+                }
+            })
+            .collect();
+        Circuit { num_inputs, gates }
+    }
+
+    fn num_multipliers(&self) -> usize {
+        self.gates
+            .iter()
+            .map(|g| match g {
+                Gate::Mul(_, _) => 1,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Evaluates the circuit forward, returning the output value
+    /// and the multiplier assignments.
+    fn evaluate(&self, inputs: &[Scalar]) -> (Scalar, Witness) {
+        let mut wires = inputs.to_vec();
+        let mut witness = Witness::new();
+        for gate in self.gates.iter() {
+            let value = match *gate {
+                Gate::Add(a, b) => wires[a] + wires[b],
+                Gate::Sub(a, b) => wires[a] - wires[b],
+                Gate::Mul(a, b) => {
+                    let (l, r) = (wires[a], wires[b]);
+                    witness.push((l, r, l * r));
+                    l * r
+                }
+            };
+            wires.push(value);
+        }
+        (wires[wires.len() - 1], witness)
+    }
+
+    /// Builds the constraints of the circuit, constraining its last
+    /// wire to equal `output`.
+    ///
+    /// The prover passes the multiplier assignments as `witness`,
+    /// the verifier passes `None`.
+    fn gadget<CS: ConstraintSystem>(
+        &self,
+        cs: &mut CS,
+        inputs: &[Variable],
+        output: Variable,
+        witness: Option<&Witness>,
+    ) -> Result<(), R1CSError> {
+        assert_eq!(inputs.len(), self.num_inputs);
+
+        let mut wires: Vec<LinearCombination> = inputs.iter().map(|v| (*v).into()).collect();
+        let mut next_multiplier = 0;
+        for gate in self.gates.iter() {
+            let wire = match *gate {
+                Gate::Add(a, b) => wires[a].clone() + wires[b].clone(),
+                Gate::Sub(a, b) => wires[a].clone() - wires[b].clone(),
+                Gate::Mul(a, b) => {
+                    let j = next_multiplier;
+                    next_multiplier += 1;
+                    let (l, r, o) =
+                        cs.allocate(|| witness.map(|w| w[j]).ok_or(R1CSError::MissingAssignment))?;
+                    cs.constrain(wires[a].clone() - l);
+                    cs.constrain(wires[b].clone() - r);
+                    o.into()
+                }
+            };
+            wires.push(wire);
+        }
+
+        let last = wires.pop().unwrap();
+        cs.constrain(last - output);
+        Ok(())
+    }
+
+    fn prove(
+        &self,
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        inputs: &[Scalar],
+        output: Scalar,
+        witness: &Witness,
+    ) -> Result<(R1CSProof, Vec<CompressedRistretto>), R1CSError> {
+        let mut transcript = Transcript::new(b"CircuitStressTest");
+        let mut prover = Prover::new(bp_gens, pc_gens, &mut transcript);
+
+        let mut blinding_rng = thread_rng();
+        let (commitments, vars): (Vec<_>, Vec<_>) = inputs
+            .iter()
+            .chain(Some(&output))
+            .map(|v| prover.commit(*v, Scalar::random(&mut blinding_rng)))
+            .unzip();
+
+        let mut cs = prover.finalize_inputs();
+        self.gadget(
+            &mut cs,
+            &vars[..self.num_inputs],
+            vars[self.num_inputs],
+            Some(witness),
+        )?;
+        let proof = cs.prove()?;
+
+        Ok((proof, commitments))
+    }
+
+    fn verify(
+        &self,
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        proof: &R1CSProof,
+        commitments: &[CompressedRistretto],
+    ) -> Result<(), R1CSError> {
+        let mut transcript = Transcript::new(b"CircuitStressTest");
+        let mut verifier = Verifier::new(bp_gens, pc_gens, &mut transcript);
+
+        let vars: Vec<_> = commitments.iter().map(|V| verifier.commit(*V)).collect();
+
+        let mut cs = verifier.finalize_inputs();
+        self.gadget(
+            &mut cs,
+            &vars[..self.num_inputs],
+            vars[self.num_inputs],
+            None,
+        )?;
+
+        cs.verify(proof)
+    }
+}
+
+/// A random circuit with its inputs, and the multiplier whose output
+/// a dishonest prover corrupts.
+#[derive(Clone, Debug)]
+struct Case {
+    circuit: Circuit,
+    inputs: Vec<u64>,
+    bad_multiplier: usize,
+}
+
+impl Arbitrary for Case {
+    fn arbitrary<G: Gen>(g: &mut G) -> Case {
+        let num_inputs = g.gen_range(1, 6);
+        let num_gates = g.gen_range(1, 25);
+        Case {
+            circuit: Circuit::random(g, num_inputs, num_gates),
+            inputs: (0..num_inputs).map(|_| g.gen()).collect(),
+            bad_multiplier: g.gen(),
+        }
+    }
+
+    /// Shrinks to the circuits made of a prefix of the gates, which
+    /// all keep the first gate, a multiplier.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Case>> {
+        let shrunk: Vec<Case> = (1..self.circuit.gates.len())
+            .map(|len| Case {
+                circuit: Circuit {
+                    num_inputs: self.circuit.num_inputs,
+                    gates: self.circuit.gates[..len].to_vec(),
+                },
+                inputs: self.inputs.clone(),
+                bad_multiplier: self.bad_multiplier,
+            })
+            .collect();
+        Box::new(shrunk.into_iter())
+    }
+}
+
+/// Checks that an honest witness satisfies the circuit, and that
+/// corrupting the output of a single multiplier does not.
+fn honest_proofs_verify_and_corrupted_fail(case: Case) -> bool {
+    let circuit = &case.circuit;
+    let inputs: Vec<Scalar> = case.inputs.iter().map(|x| Scalar::from(*x)).collect();
+    let (output, witness) = circuit.evaluate(&inputs);
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(circuit.num_multipliers().next_power_of_two(), 1);
+
+    let honest = circuit
+        .prove(&pc_gens, &bp_gens, &inputs, output, &witness)
+        .and_then(|(proof, commitments)| circuit.verify(&pc_gens, &bp_gens, &proof, &commitments));
+    if honest.is_err() {
+        return false;
+    }
+
+    let mut bad_witness = witness.clone();
+    bad_witness[case.bad_multiplier % witness.len()].2 += Scalar::one();
+    let dishonest = circuit
+        .prove(&pc_gens, &bp_gens, &inputs, output, &bad_witness)
+        .and_then(|(proof, commitments)| circuit.verify(&pc_gens, &bp_gens, &proof, &commitments));
+    dishonest == Err(R1CSError::VerificationError)
+}
+
+#[test]
+fn random_circuits() {
+    QuickCheck::new()
+        .tests(32)
+        .quickcheck(honest_proofs_verify_and_corrupted_fail as fn(Case) -> bool);
+}
@@ -0,0 +1,98 @@
+#![allow(non_snake_case)]
+
+extern crate rand;
+use rand::thread_rng;
+
+extern crate curve25519_dalek;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+
+extern crate merlin;
+use merlin::Transcript;
+
+extern crate bulletproofs;
+use bulletproofs::{BulletproofGens, InnerProductProof, PedersenGens, RangeProof};
+
+#[test]
+fn fold_challenges_from_public_api() {
+    let n = 16;
+    let mut rng = thread_rng();
+    let bp_gens = BulletproofGens::new(n, 1);
+    let G: Vec<RistrettoPoint> = bp_gens.G(n, 1).cloned().collect();
+    let H: Vec<RistrettoPoint> = bp_gens.H(n, 1).cloned().collect();
+    let Q = PedersenGens::default().B_blinding;
+
+    let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+    let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+    let c = a
+        .iter()
+        .zip(&b)
+        .fold(Scalar::zero(), |acc, (a_i, b_i)| acc + a_i * b_i);
+    let P = RistrettoPoint::vartime_multiscalar_mul(
+        a.iter().chain(&b).chain(Some(&c)),
+        G.iter().chain(&H).chain(Some(&Q)),
+    );
+    let Hprime_factors = vec![Scalar::one(); n];
+
+    let proof = InnerProductProof::create(
+        &mut Transcript::new(b"IPPPublicTest"),
+        &Q,
+        &Hprime_factors,
+        G.clone(),
+        H.clone(),
+        a,
+        b,
+    );
+    assert_eq!(proof.depth(), 4);
+    assert_eq!(proof.dimension(), n);
+
+    let challenges = proof
+        .fold_challenges(n, &mut Transcript::new(b"IPPPublicTest"))
+        .unwrap();
+    assert_eq!(challenges.len(), proof.depth());
+    for (u, u_inv) in &challenges {
+        assert_eq!(u * u_inv, Scalar::one());
+    }
+    assert!(proof
+        .fold_challenges(2 * n, &mut Transcript::new(b"IPPPublicTest"))
+        .is_err());
+
+    // The challenges match the ones drawn by the verifier.
+    let mut transcript = Transcript::new(b"IPPPublicTest");
+    assert!(proof
+        .verify(n, &mut transcript, &Hprime_factors, &P, &Q, &G, &H)
+        .is_ok());
+    let mut replayed = Transcript::new(b"IPPPublicTest");
+    proof.fold_challenges(n, &mut replayed).unwrap();
+    let mut buf = [0u8; 32];
+    let mut other = [0u8; 32];
+    transcript.challenge_bytes(b"next", &mut buf);
+    replayed.challenge_bytes(b"next", &mut other);
+    assert_eq!(buf, other);
+}
+
+#[test]
+fn range_proof_inner_product_proof() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 4);
+    let mut rng = thread_rng();
+    let blindings: Vec<_> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+
+    let (proof, _) = RangeProof::prove_multiple(
+        &bp_gens,
+        &pc_gens,
+        &mut Transcript::new(b"IPPPublicTest"),
+        &[1, 2, 3, 4],
+        &blindings,
+        32,
+    )
+    .unwrap();
+    let ipp = proof.inner_product_proof();
+    assert_eq!(ipp.dimension(), 32 * 4);
+    assert_eq!(ipp.depth(), 7);
+    assert_eq!(
+        proof.to_bytes().len(),
+        7 * 32 + InnerProductProof::size_in_bytes(ipp.dimension())
+    );
+}
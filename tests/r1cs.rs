@@ -306,3 +306,580 @@ fn example_gadget_test() {
     // (3 + 4) * (6 + 1) != (40 + 10)
     assert!(example_gadget_roundtrip_helper(3, 4, 6, 1, 40, 10).is_err());
 }
+
+#[test]
+fn commit_batch_roundtrip() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(1, 1);
+
+    let values: Vec<Scalar> = [3u64, 4, 7].iter().map(|x| Scalar::from(*x)).collect();
+    let blindings: Vec<Scalar> = (0..3).map(|_| Scalar::random(&mut thread_rng())).collect();
+
+    let (proof, commitments) = {
+        let mut transcript = Transcript::new(b"R1CSCommitBatch");
+        let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+
+        assert_eq!(
+            prover.commit_batch(&values, &blindings[..2]).err(),
+            Some(R1CSError::WrongNumBlindingFactors)
+        );
+        let (commitments, vars) = prover.commit_batch(&values, &blindings).unwrap();
+        for ((V, v), v_blinding) in commitments.iter().zip(&values).zip(&blindings) {
+            assert_eq!(*V, pc_gens.commit(*v, *v_blinding).compress());
+        }
+
+        let mut cs = prover.finalize_inputs();
+        cs.constrain(vars[0] + vars[1] - vars[2]);
+        (cs.prove().unwrap(), commitments)
+    };
+
+    // Verify with one-by-one commitments, which must be equivalent.
+    let mut transcript = Transcript::new(b"R1CSCommitBatch");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let vars: Vec<_> = commitments.iter().map(|V| verifier.commit(*V)).collect();
+    let mut cs = verifier.finalize_inputs();
+    cs.constrain(vars[0] + vars[1] - vars[2]);
+    assert!(cs.verify(&proof).is_ok());
+
+    let mut transcript = Transcript::new(b"R1CSCommitBatch");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let vars = verifier.commit_batch(&commitments);
+    let mut cs = verifier.finalize_inputs();
+    cs.constrain(vars[0] + vars[1] - vars[2]);
+    assert!(cs.verify(&proof).is_ok());
+}
+
+#[test]
+fn constraint_system_size_inspection() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(2, 1);
+
+    let (proof, commitments) = {
+        let mut transcript = Transcript::new(b"R1CSSizeInspection");
+        let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+        let (commitments, vars): (Vec<_>, Vec<_>) = (0..3u64)
+            .map(|x| prover.commit(Scalar::from(x), Scalar::random(&mut thread_rng())))
+            .unzip();
+
+        let mut cs = prover.finalize_inputs();
+        let (_, _, o) = cs.multiply(vars[0].into(), vars[1].into());
+        cs.constrain(o - vars[0]);
+        assert_eq!(cs.committed_len(), 3);
+        assert_eq!(cs.multipliers_len(), 1);
+        assert_eq!(cs.constraints_len(), 3);
+        assert_eq!(cs.total_verification_cost(), 6);
+
+        (cs.prove().unwrap(), commitments)
+    };
+
+    let mut transcript = Transcript::new(b"R1CSSizeInspection");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let vars = verifier.commit_batch(&commitments);
+    let mut cs = verifier.finalize_inputs();
+    let (_, _, o) = cs.multiply(vars[0].into(), vars[1].into());
+    cs.constrain(o - vars[0]);
+    assert_eq!(cs.committed_len(), 3);
+    assert_eq!(cs.multipliers_len(), 1);
+    assert_eq!(cs.constraints_len(), 3);
+    assert_eq!(cs.total_verification_cost(), 6);
+
+    assert!(cs.verify(&proof).is_ok());
+}
+
+/// Returns the change in the size of `cs` from `before`.
+fn stats_delta<CS: ConstraintSystem>(
+    cs: &CS,
+    before: ConstraintSystemStats,
+) -> ConstraintSystemStats {
+    let after = cs.stats();
+    ConstraintSystemStats {
+        multiplier_count: after.multiplier_count - before.multiplier_count,
+        linear_constraint_count: after.linear_constraint_count - before.linear_constraint_count,
+        input_variable_count: after.input_variable_count - before.input_variable_count,
+        allocated_variable_count: after.allocated_variable_count - before.allocated_variable_count,
+    }
+}
+
+#[test]
+fn constraint_system_stats() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(16, 1);
+    let mut transcript = Transcript::new(b"R1CSStats");
+    let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+    let (_, vars) = prover
+        .commit_batch(
+            &[Scalar::from(3u64), Scalar::from(27u64)],
+            &[
+                Scalar::random(&mut thread_rng()),
+                Scalar::random(&mut thread_rng()),
+            ],
+        )
+        .unwrap();
+    let mut cs = prover.finalize_inputs();
+
+    let before = cs.stats();
+    assert_eq!(
+        before,
+        ConstraintSystemStats {
+            input_variable_count: 2,
+            ..Default::default()
+        }
+    );
+
+    // The example gadget costs one multiplier and three constraints.
+    let one = || LinearCombination::from(Scalar::one());
+    example_gadget(&mut cs, one(), one(), one(), one(), one(), one() * 3u64);
+    let delta = stats_delta(&cs, before);
+    assert_eq!(
+        delta,
+        ConstraintSystemStats {
+            multiplier_count: 1,
+            linear_constraint_count: 3,
+            input_variable_count: 0,
+            allocated_variable_count: 3,
+        }
+    );
+
+    // The power gadget costs `m` multipliers, with two constraints
+    // each for their inputs, plus the constraint on the output.
+    let before = cs.stats();
+    power_gadget(&mut cs, vars[0], vars[1], 2);
+    let delta = stats_delta(&cs, before);
+    assert_eq!(delta.multiplier_count, 2);
+    assert_eq!(delta.linear_constraint_count, 2 * 2 + 1);
+    assert_eq!(delta.allocated_variable_count, 6);
+
+    // Budgeting does not change the counts.
+    let stats = cs.stats();
+    assert_eq!(cs.with_budget(16).stats(), stats);
+    assert_eq!(
+        stats.to_string(),
+        "multipliers                   3\n\
+         linear constraints            8\n\
+         input variables               2\n\
+         allocated variables           9"
+    );
+    assert!(cs.prove().is_ok());
+}
+
+/// Speculatively adds an unsatisfiable constraint in a fork that is
+/// discarded, then adds `x * y = z` in a fork that is merged back.
+fn fork_gadget<CS: ConstraintSystem>(cs: &mut CS, x: Variable, y: Variable, z: Variable) {
+    let (multipliers, constraints) = (cs.multipliers_len(), cs.constraints_len());
+
+    let mut discarded = cs.fork();
+    let (_, _, o) = discarded.multiply(x.into(), x.into());
+    discarded.constrain(o - z + Scalar::one());
+    drop(discarded);
+    assert_eq!(cs.multipliers_len(), multipliers);
+    assert_eq!(cs.constraints_len(), constraints);
+
+    let mut fork = cs.fork();
+    let w = fork.challenge_scalar(b"fork challenge");
+    let (_, _, o) = fork.multiply(x.into(), y * w);
+    fork.constrain(o - z * w);
+    assert_eq!(fork.committed_len(), cs.committed_len());
+    assert_eq!(fork.multipliers_len(), multipliers + 1);
+    cs.merge_fork(fork);
+    assert_eq!(cs.multipliers_len(), multipliers + 1);
+    assert_eq!(cs.constraints_len(), constraints + 3);
+}
+
+fn fork_gadget_roundtrip_helper(x: u64, y: u64, z: u64) -> Result<(), R1CSError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(2, 1);
+
+    let (proof, commitments) = {
+        let mut transcript = Transcript::new(b"R1CSFork");
+        let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+        let (commitments, vars): (Vec<_>, Vec<_>) = [x, y, z]
+            .iter()
+            .map(|v| prover.commit(Scalar::from(*v), Scalar::random(&mut thread_rng())))
+            .unzip();
+
+        let mut cs = prover.finalize_inputs();
+        fork_gadget(&mut cs, vars[0], vars[1], vars[2]);
+        (cs.prove()?, commitments)
+    };
+
+    let mut transcript = Transcript::new(b"R1CSFork");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let vars = verifier.commit_batch(&commitments);
+    let mut cs = verifier.finalize_inputs();
+    fork_gadget(&mut cs, vars[0], vars[1], vars[2]);
+    cs.verify(&proof)
+}
+
+#[test]
+fn fork_and_merge() {
+    assert!(fork_gadget_roundtrip_helper(3, 4, 12).is_ok());
+    assert!(fork_gadget_roundtrip_helper(3, 4, 13).is_err());
+}
+
+#[test]
+#[should_panic(expected = "fork was not created from the current state")]
+fn merge_fork_after_challenge() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(2, 1);
+    let mut transcript = Transcript::new(b"R1CSFork");
+    let mut cs = Verifier::new(&bp_gens, &pc_gens, &mut transcript).finalize_inputs();
+
+    // Drawing a challenge modifies the transcript, even though the
+    // sizes of the constraint system are unchanged.
+    let fork = cs.fork();
+    cs.challenge_scalar(b"challenge");
+    cs.merge_fork(fork);
+}
+
+#[test]
+#[should_panic(expected = "fork was not created from the current state")]
+fn merge_fork_from_other_constraint_system() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(2, 1);
+    let mut transcript = Transcript::new(b"R1CSFork");
+    let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+    let (_, x) = prover.commit(Scalar::one(), Scalar::random(&mut thread_rng()));
+    let mut cs = prover.finalize_inputs();
+    cs.constrain(x - Scalar::one());
+
+    let mut other_transcript = Transcript::new(b"R1CSFork");
+    let mut other = Prover::new(&bp_gens, &pc_gens, &mut other_transcript);
+    let (_, y) = other.commit(Scalar::one(), Scalar::random(&mut thread_rng()));
+    let mut other = other.finalize_inputs();
+    other.constrain(y - Scalar::one());
+
+    // Both constraint systems have the same sizes.
+    let fork = other.fork();
+    cs.merge_fork(fork);
+}
+
+fn assert_nonzero_roundtrip_helper(x: u64) -> Result<(), R1CSError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(1, 1);
+
+    let (proof, commitment) = {
+        let mut transcript = Transcript::new(b"R1CSAssertNonzero");
+        let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+        let (commitment, var) = prover.commit(Scalar::from(x), Scalar::random(&mut thread_rng()));
+        let mut cs = prover.finalize_inputs();
+        cs.assert_nonzero(var)?;
+        assert_eq!(cs.multipliers_len(), 1);
+        (cs.prove()?, commitment)
+    };
+
+    let mut transcript = Transcript::new(b"R1CSAssertNonzero");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let var = verifier.commit(commitment);
+    let mut cs = verifier.finalize_inputs();
+    cs.assert_nonzero(var)?;
+    cs.verify(&proof)
+}
+
+#[test]
+fn assert_nonzero() {
+    assert!(assert_nonzero_roundtrip_helper(1).is_ok());
+    assert!(assert_nonzero_roundtrip_helper(7).is_ok());
+    assert_eq!(
+        assert_nonzero_roundtrip_helper(0),
+        Err(R1CSError::WitnessIsZero)
+    );
+}
+
+/// Returns the commitment to `value` with a zero blinding factor,
+/// using the generators of the constraint system.
+fn constant_commitment<CS: ConstraintSystem>(cs: &CS, value: Scalar) -> CompressedRistretto {
+    cs.pedersen_gens().commit(value, Scalar::zero()).compress()
+}
+
+/// Constrains `var` to equal `value`, and returns the commitment to
+/// `value` computed in a fork of `cs`.
+fn constant_gadget<CS: ConstraintSystem>(
+    cs: &mut CS,
+    var: Variable,
+    value: Scalar,
+) -> CompressedRistretto {
+    cs.constrain(var - value);
+    constant_commitment(&cs.fork(), value)
+}
+
+#[test]
+fn pedersen_gens_from_constraint_system() {
+    let default_gens = PedersenGens::default();
+    let pc_gens = PedersenGens {
+        B: default_gens.B_blinding,
+        B_blinding: default_gens.B,
+    };
+    let bp_gens = BulletproofGens::new(1, 1);
+    let value = Scalar::from(5u64);
+    let expected = pc_gens.commit(value, Scalar::zero()).compress();
+
+    let (proof, commitment) = {
+        let mut transcript = Transcript::new(b"R1CSPedersenGens");
+        let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+        let (commitment, var) = prover.commit(value, Scalar::random(&mut thread_rng()));
+        let mut cs = prover.finalize_inputs();
+        assert_eq!(constant_gadget(&mut cs, var, value), expected);
+        (cs.prove().unwrap(), commitment)
+    };
+
+    let mut transcript = Transcript::new(b"R1CSPedersenGens");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let var = verifier.commit(commitment);
+    let mut cs = verifier.finalize_inputs();
+    assert_eq!(constant_gadget(&mut cs, var, value), expected);
+    assert!(cs.verify(&proof).is_ok());
+}
+
+#[test]
+fn variable_witness_indices() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(4, 1);
+    let mut transcript = Transcript::new(b"R1CSWitnessIndices");
+    let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+
+    let (_, vars) = prover
+        .commit_batch(
+            &[Scalar::one(), Scalar::one()],
+            &[Scalar::one(), Scalar::one()],
+        )
+        .unwrap();
+    assert_eq!(vars[0].witness_index(), Some(0));
+    assert_eq!(vars[1].witness_index(), Some(1));
+
+    let mut cs = prover.finalize_inputs();
+    let mut outputs = Vec::new();
+    for _ in 0..3 {
+        let (l, r, o) = cs.multiply(vars[0].into(), vars[1].into());
+        assert_eq!(l.witness_index(), o.witness_index());
+        assert_eq!(r.witness_index(), o.witness_index());
+        outputs.push(o);
+    }
+    assert!(outputs.windows(2).all(|w| w[0] < w[1]));
+    assert!(outputs
+        .windows(2)
+        .all(|w| w[0].witness_index() < w[1].witness_index()));
+    assert_eq!(Variable::One().witness_index(), None);
+}
+
+/// Constrains `y = x^(m+1)` with a chain of `m` multipliers.
+fn power_gadget<CS: ConstraintSystem>(cs: &mut CS, x: Variable, y: Variable, m: usize) {
+    let mut acc: LinearCombination = x.into();
+    for _ in 0..m {
+        let (_, _, o) = cs.multiply(acc, x.into());
+        acc = o.into();
+    }
+    cs.constrain(acc - y);
+}
+
+fn budgeted_power_proof(x: u64, m: usize, budget: usize) -> Result<(), R1CSError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+
+    let (proof, commitments) = {
+        let mut transcript = Transcript::new(b"R1CSBudget");
+        let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+        let blindings: Vec<_> = (0..2).map(|_| Scalar::random(&mut thread_rng())).collect();
+        let values = [Scalar::from(x), Scalar::from(x.pow(m as u32 + 1))];
+        let (commitments, vars) = prover.commit_batch(&values, &blindings)?;
+        let mut cs = prover.finalize_inputs();
+        {
+            let mut cs = cs.with_budget(budget);
+            power_gadget(&mut cs, vars[0], vars[1], m);
+            cs.finish()?;
+        }
+        (cs.prove()?, commitments)
+    };
+
+    let mut transcript = Transcript::new(b"R1CSBudget");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let vars = verifier.commit_batch(&commitments);
+    let mut cs = verifier.finalize_inputs();
+    {
+        let mut cs = cs.with_budget(budget);
+        power_gadget(&mut cs, vars[0], vars[1], m);
+        cs.finish()?;
+    }
+    cs.verify(&proof)
+}
+
+#[test]
+fn multiplier_budget() {
+    assert!(budgeted_power_proof(3, 3, 3).is_ok());
+    assert!(budgeted_power_proof(3, 3, 8).is_ok());
+    assert_eq!(
+        budgeted_power_proof(3, 4, 3),
+        Err(R1CSError::BudgetExceeded { used: 4, budget: 3 })
+    );
+
+    // Allocation fails as soon as the budget is spent.
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(2, 1);
+    let mut transcript = Transcript::new(b"R1CSBudget");
+    let prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+    let mut cs = prover.finalize_inputs();
+    let mut cs = cs.with_budget(1);
+    let one = Scalar::one();
+    assert!(cs.allocate(|| Ok((one, one, one))).is_ok());
+    assert_eq!(cs.remaining(), 0);
+    assert_eq!(
+        cs.allocate(|| Ok((one, one, one))),
+        Err(R1CSError::BudgetExceeded { used: 2, budget: 1 })
+    );
+    assert_eq!(
+        cs.assert_nonzero(Variable::One()),
+        Err(R1CSError::BudgetExceeded { used: 2, budget: 1 })
+    );
+    assert_eq!(cs.multipliers_len(), 1);
+    assert!(cs.finish().is_ok());
+}
+
+#[test]
+fn multiplier_budget_try() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(4, 1);
+    let mut transcript = Transcript::new(b"R1CSBudget");
+    let prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+    let mut cs = prover.finalize_inputs();
+    let mut cs = cs.with_budget(2);
+    let one = LinearCombination::from(Scalar::one());
+
+    assert!(cs.try_multiply(one.clone(), one.clone()).is_ok());
+
+    // A fork over budget is not merged.
+    let mut fork = cs.fork();
+    fork.multiply(one.clone(), one.clone());
+    fork.multiply(one.clone(), one.clone());
+    assert_eq!(
+        cs.try_merge_fork(fork),
+        Err(R1CSError::BudgetExceeded { used: 3, budget: 2 })
+    );
+    assert_eq!(cs.multipliers_len(), 1);
+
+    let mut fork = cs.fork();
+    fork.multiply(one.clone(), one.clone());
+    assert!(cs.try_merge_fork(fork).is_ok());
+    assert_eq!(
+        cs.try_multiply(one.clone(), one.clone()).err(),
+        Some(R1CSError::BudgetExceeded { used: 3, budget: 2 })
+    );
+    assert_eq!(cs.multipliers_len(), 2);
+    assert!(cs.finish().is_ok());
+}
+
+fn power_proof(
+    bp_gens: &BulletproofGens,
+    pc_gens: &PedersenGens,
+    x: u64,
+    y: u64,
+    m: usize,
+) -> (R1CSProof, Vec<CompressedRistretto>) {
+    let mut transcript = Transcript::new(b"R1CSBatchVerify");
+    let mut prover = Prover::new(bp_gens, pc_gens, &mut transcript);
+    let blindings: Vec<_> = (0..2).map(|_| Scalar::random(&mut thread_rng())).collect();
+    let (commitments, vars) = prover
+        .commit_batch(&[Scalar::from(x), Scalar::from(y)], &blindings)
+        .unwrap();
+    let mut cs = prover.finalize_inputs();
+    power_gadget(&mut cs, vars[0], vars[1], m);
+    (cs.prove().unwrap(), commitments)
+}
+
+fn batch_verify_helper(
+    bp_gens: &BulletproofGens,
+    pc_gens: &PedersenGens,
+    proofs: &[(R1CSProof, Vec<CompressedRistretto>)],
+    ms: &[usize],
+) -> Result<(), R1CSError> {
+    let mut transcripts: Vec<_> = ms
+        .iter()
+        .map(|_| Transcript::new(b"R1CSBatchVerify"))
+        .collect();
+    let instances = transcripts.iter_mut().zip(proofs).zip(ms).map(
+        |((transcript, (proof, commitments)), &m)| {
+            let mut verifier = Verifier::new(bp_gens, pc_gens, transcript);
+            let vars = verifier.commit_batch(commitments);
+            let mut cs = verifier.finalize_inputs();
+            power_gadget(&mut cs, vars[0], vars[1], m);
+            (cs, proof)
+        },
+    );
+    batch_verify(instances)
+}
+
+#[test]
+fn batch_verification() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+
+    // Circuits of different sizes can be verified together.
+    let ms = [1, 2, 3, 5];
+    let mut proofs: Vec<_> = ms
+        .iter()
+        .map(|&m| power_proof(&bp_gens, &pc_gens, 3, 3u64.pow(m as u32 + 1), m))
+        .collect();
+    assert!(batch_verify_helper(&bp_gens, &pc_gens, &proofs, &ms).is_ok());
+    assert!(batch_verify_helper(&bp_gens, &pc_gens, &[], &[]).is_ok());
+
+    // A single invalid proof makes the batch fail.
+    proofs[2] = power_proof(&bp_gens, &pc_gens, 3, 82, 3);
+    assert_eq!(
+        batch_verify_helper(&bp_gens, &pc_gens, &proofs, &ms),
+        Err(R1CSError::VerificationError)
+    );
+
+    // So does a valid proof for the wrong circuit.
+    proofs[2] = power_proof(&bp_gens, &pc_gens, 3, 243, 4);
+    assert_eq!(
+        batch_verify_helper(&bp_gens, &pc_gens, &proofs, &ms),
+        Err(R1CSError::VerificationError)
+    );
+}
+
+#[test]
+fn batch_verification_generators() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+    let other_bp_gens = BulletproofGens::new(8, 1);
+    let small_bp_gens = BulletproofGens::new(2, 1);
+    let swapped_pc_gens = PedersenGens {
+        B: pc_gens.B_blinding,
+        B_blinding: pc_gens.B,
+    };
+
+    let verify = |gens: &[(&BulletproofGens, &PedersenGens, usize)]| {
+        let proofs: Vec<_> = gens
+            .iter()
+            .map(|&(bp_gens, pc_gens, m)| {
+                power_proof(bp_gens, pc_gens, 3, 3u64.pow(m as u32 + 1), m)
+            })
+            .collect();
+        let mut transcripts: Vec<_> = gens
+            .iter()
+            .map(|_| Transcript::new(b"R1CSBatchVerify"))
+            .collect();
+        let instances = transcripts.iter_mut().zip(gens).zip(&proofs).map(
+            |((transcript, &(bp_gens, pc_gens, m)), (proof, commitments))| {
+                let mut verifier = Verifier::new(bp_gens, pc_gens, transcript);
+                let vars = verifier.commit_batch(commitments);
+                let mut cs = verifier.finalize_inputs();
+                power_gadget(&mut cs, vars[0], vars[1], m);
+                (cs, proof)
+            },
+        );
+        batch_verify(instances)
+    };
+
+    // Generators that are equal but separately constructed can be
+    // used together.
+    assert!(verify(&[(&bp_gens, &pc_gens, 3), (&other_bp_gens, &pc_gens, 3)]).is_ok());
+
+    assert_eq!(
+        verify(&[(&bp_gens, &pc_gens, 3), (&bp_gens, &swapped_pc_gens, 3)]),
+        Err(R1CSError::GeneratorsMismatch)
+    );
+    // The generators of the first proof are used for the whole batch.
+    assert_eq!(
+        verify(&[(&small_bp_gens, &pc_gens, 1), (&bp_gens, &pc_gens, 3)]),
+        Err(R1CSError::InvalidGeneratorsLength)
+    );
+}
@@ -0,0 +1,84 @@
+#![allow(non_snake_case)]
+extern crate bulletproofs;
+extern crate curve25519_dalek;
+extern crate merlin;
+extern crate rand;
+
+use bulletproofs::r1cs::gadgets::rsa::rsa_verify;
+use bulletproofs::r1cs::*;
+use bulletproofs::{BulletproofGens, PedersenGens};
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand::thread_rng;
+
+/// Returns the `k` little-endian limbs of `limb_bits` bits of `value`.
+fn to_limbs(value: u64, k: usize, limb_bits: usize) -> Vec<u64> {
+    (0..k)
+        .map(|i| (value >> (i * limb_bits)) & ((1 << limb_bits) - 1))
+        .collect()
+}
+
+/// Returns the limbs of `value` as scalars.
+fn to_scalar_limbs(value: u64, k: usize, limb_bits: usize) -> Vec<Scalar> {
+    to_limbs(value, k, limb_bits)
+        .into_iter()
+        .map(Scalar::from)
+        .collect()
+}
+
+fn rsa_roundtrip_helper(
+    x: u64,
+    y: u64,
+    e: u64,
+    N: u64,
+    k: usize,
+    limb_bits: usize,
+) -> Result<(), R1CSError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(2048, 1);
+    let (y, N) = (
+        to_scalar_limbs(y, k, limb_bits),
+        to_scalar_limbs(N, k, limb_bits),
+    );
+
+    let (proof, commitments) = {
+        let mut transcript = Transcript::new(b"R1CSRsaPreimage");
+        let mut prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+        let x = to_limbs(x, k, limb_bits);
+        let values: Vec<_> = x.iter().map(|&x_i| Scalar::from(x_i)).collect();
+        let blindings: Vec<_> = (0..k).map(|_| Scalar::random(&mut thread_rng())).collect();
+        let (commitments, vars) = prover.commit_batch(&values, &blindings)?;
+
+        let mut cs = prover.finalize_inputs();
+        rsa_verify(&mut cs, &vars, Some(&x), &y, e, &N, limb_bits)?;
+        (cs.prove()?, commitments)
+    };
+
+    let mut transcript = Transcript::new(b"R1CSRsaPreimage");
+    let mut verifier = Verifier::new(&bp_gens, &pc_gens, &mut transcript);
+    let vars = verifier.commit_batch(&commitments);
+    let mut cs = verifier.finalize_inputs();
+    rsa_verify(&mut cs, &vars, None, &y, e, &N, limb_bits)?;
+    cs.verify(&proof)
+}
+
+#[test]
+fn rsa_preimage() {
+    // 94747 is the largest prime factor of 3^17 - 2.
+    assert!(rsa_roundtrip_helper(3, 2, 17, 94747, 3, 8).is_ok());
+    assert!(rsa_roundtrip_helper(3, 3, 17, 94747, 3, 8).is_err());
+    assert!(rsa_roundtrip_helper(3, 2, 16, 94747, 3, 8).is_err());
+}
+
+#[test]
+fn rsa_preimage_mersenne_61() {
+    let N = (1u64 << 61) - 1;
+    let x = 0x0123_4567_89ab_cdef;
+    let mut y = 1u128;
+    for _ in 0..17 {
+        y = y * u128::from(x) % u128::from(N);
+    }
+    let y = y as u64;
+    assert!(rsa_roundtrip_helper(x, y, 17, N, 4, 16).is_ok());
+    assert!(rsa_roundtrip_helper(x, y ^ 1, 17, N, 4, 16).is_err());
+}
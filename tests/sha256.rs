@@ -0,0 +1,83 @@
+#![allow(non_snake_case)]
+extern crate bulletproofs;
+extern crate curve25519_dalek;
+extern crate hex;
+extern crate merlin;
+extern crate rand;
+
+use bulletproofs::r1cs::gadgets::allocate_bits;
+use bulletproofs::r1cs::gadgets::sha256::sha256_gadget;
+use bulletproofs::r1cs::*;
+use bulletproofs::{BulletproofGens, PedersenGens};
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+/// Allocates the bits of `message`, most significant bit of each byte
+/// first, for a message of `len` bytes.
+fn allocate_message<CS: ConstraintSystem>(
+    cs: &mut CS,
+    message: Option<&[u8]>,
+    len: usize,
+) -> Result<Vec<Variable>, R1CSError> {
+    let mut bits = Vec::with_capacity(8 * len);
+    for i in 0..len {
+        let (byte_bits, _) = allocate_bits(cs, message.map(|m| u64::from(m[i])), 8)?;
+        bits.extend(byte_bits.into_iter().rev());
+    }
+    Ok(bits)
+}
+
+/// Constrains `bits` to the bits of `digest`, most significant bit of
+/// each byte first.
+fn constrain_digest<CS: ConstraintSystem>(cs: &mut CS, bits: &[Variable], digest: &[u8]) {
+    assert_eq!(bits.len(), 8 * digest.len());
+    for (i, &bit) in bits.iter().enumerate() {
+        let value = (digest[i / 8] >> (7 - i % 8)) & 1;
+        cs.constrain(bit - Scalar::from(value));
+    }
+}
+
+fn sha256_preimage_verify(
+    bp_gens: &BulletproofGens,
+    pc_gens: &PedersenGens,
+    proof: &R1CSProof,
+    len: usize,
+    digest: &[u8],
+) -> Result<(), R1CSError> {
+    let mut transcript = Transcript::new(b"R1CSSha256Preimage");
+    let verifier = Verifier::new(bp_gens, pc_gens, &mut transcript);
+    let mut cs = verifier.finalize_inputs();
+    let input_bits = allocate_message(&mut cs, None, len)?;
+    let digest_bits = sha256_gadget(&mut cs, &input_bits, None)?;
+    constrain_digest(&mut cs, &digest_bits, digest);
+    cs.verify(proof)
+}
+
+#[test]
+fn sha256_preimage() {
+    let message = b"abc";
+    let digest =
+        hex::decode("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad").unwrap();
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32768, 1);
+
+    let proof = {
+        let mut transcript = Transcript::new(b"R1CSSha256Preimage");
+        let prover = Prover::new(&bp_gens, &pc_gens, &mut transcript);
+        let mut cs = prover.finalize_inputs();
+        let input_bits = allocate_message(&mut cs, Some(message), message.len()).unwrap();
+        let digest_bits = sha256_gadget(&mut cs, &input_bits, Some(message)).unwrap();
+        constrain_digest(&mut cs, &digest_bits, &digest);
+        assert_eq!(cs.multipliers_len(), 26_880);
+        cs.prove().unwrap()
+    };
+
+    assert!(sha256_preimage_verify(&bp_gens, &pc_gens, &proof, message.len(), &digest).is_ok());
+
+    let mut wrong_digest = digest.clone();
+    wrong_digest[31] ^= 1;
+    assert!(
+        sha256_preimage_verify(&bp_gens, &pc_gens, &proof, message.len(), &wrong_digest).is_err()
+    );
+}